@@ -0,0 +1,26 @@
+#import bevy_pbr::{
+	forward_io::VertexOutput,
+	mesh_view_bindings::view,
+}
+
+struct BeamMaterial {
+	color: vec4<f32>,
+};
+
+@group(2) @binding(0) var<uniform> material: BeamMaterial;
+
+// Fog is not applied, beams are meant to be seen from afar through the storm
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+	let to_camera = normalize(view.world_position.xyz - in.world_position.xyz);
+	// Brighter through the middle of the beam where it looks thicker, faded on its edges
+	let facing = abs(dot(normalize(in.world_normal), to_camera));
+	let thickness = facing * facing;
+	// v goes from 0 at the base to 1 at the top, where the beam vanishes
+	let height = in.uv.y;
+	let fade = smoothstep(0.0, 0.02, height) * pow(1.0 - height, 2.0);
+
+	let alpha = material.color.a * thickness * fade;
+	// Added to what is behind, premultiplied
+	return vec4<f32>(material.color.rgb * alpha, 0.0);
+}
@@ -0,0 +1,23 @@
+#import bevy_pbr::{
+	forward_io::VertexOutput,
+	mesh_view_bindings::view,
+}
+
+struct GlowMaterial {
+	color: vec4<f32>,
+};
+
+@group(2) @binding(0) var<uniform> material: GlowMaterial;
+
+// Fog is not applied, glows are meant to be seen from afar through the storm
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+	let to_camera = normalize(view.world_position.xyz - in.world_position.xyz);
+	// Bright core fading out toward the silhouette of the sphere
+	let facing = max(dot(normalize(in.world_normal), to_camera), 0.0);
+	let glow = pow(facing, 3.0);
+
+	let alpha = material.color.a * glow;
+	// Added to what is behind, premultiplied
+	return vec4<f32>(material.color.rgb * alpha, 0.0);
+}
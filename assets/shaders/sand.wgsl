@@ -112,7 +112,12 @@ fn fragment(
 
 
 
-	pbr_input.material.base_color = vec4f(mix(SAND_LIGHT, SAND_DARK, noise3(pos)), 1.0);
+	var sand = mix(SAND_LIGHT, SAND_DARK, noise3(pos));
+#ifdef VERTEX_COLORS
+	// Biome tint, its alpha is how much it replaces the sand
+	sand = mix(sand, in.color.rgb, in.color.a);
+#endif
+	pbr_input.material.base_color = vec4f(sand, 1.0);
 	pbr_input.material.perceptual_roughness = (noise3(pos + vec3f(300.0, 0.0, 0.0))*0.5+0.5);
 
 #ifdef PREPASS_PIPELINE
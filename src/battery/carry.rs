@@ -0,0 +1,134 @@
+use avian3d::prelude::{Collider, LinearVelocity, RigidBody, Sensor};
+use bevy::{pbr::NotShadowCaster, prelude::*};
+
+use crate::{
+    camera::MainCamera,
+    movement::Speed,
+    player::{Inventory, Player, PLAYER_SPEED},
+    terrain::OnTerrain,
+};
+
+/// Batteries the player can carry at once
+pub const MAX_CARRIED: usize = 3;
+// Fraction of the player speed lost per battery carried
+const SPEED_PENALTY: f32 = 0.15;
+// Where the held batteries are shown relative to the camera, the last one taken on the right
+const HELD_OFFSET: Vec3 = Vec3::new(0.45, -0.4, -1.0);
+const HELD_SPACING: f32 = 0.22;
+const HELD_SCALE: f32 = 0.1;
+// Dropped batteries are thrown from this far in front of the camera
+const DROP_DISTANCE: f32 = 2.0;
+const DROP_SPEED: f32 = 4.0;
+
+/// Shown in front of the camera while in the inventory
+#[derive(Component)]
+pub struct HeldBattery;
+
+/// Where a battery was taken from since the last checkpoint, it goes back there when that
+/// checkpoint is loaded
+#[derive(Component)]
+pub struct TakenFrom {
+    pub parent: Option<Entity>,
+    pub transform: Transform,
+    pub on_terrain: bool,
+}
+
+pub fn set_casts_shadows(
+    cmds: &mut Commands,
+    q_children: &Query<&Children>,
+    e: Entity,
+    casts: bool,
+) {
+    for e in std::iter::once(e).chain(q_children.iter_descendants(e)) {
+        match casts {
+            true => cmds.entity(e).remove::<NotShadowCaster>(),
+            false => cmds.entity(e).insert(NotShadowCaster),
+        };
+    }
+}
+
+/// The inventory decides which batteries are held, including when a checkpoint is loaded
+pub fn hold_batteries(
+    mut cmds: Commands,
+    q_inventory: Query<&Inventory, Changed<Inventory>>,
+    q_camera: Query<Entity, With<MainCamera>>,
+    q_held: Query<(Entity, &TakenFrom), With<HeldBattery>>,
+    q_children: Query<&Children>,
+) {
+    let (Ok(inventory), Ok(camera_e)) = (q_inventory.get_single(), q_camera.get_single()) else {
+        return;
+    };
+
+    // Taken since the checkpoint that was loaded, these can be taken again where they were
+    for (e, taken_from) in &q_held {
+        if inventory.batteries.contains(&e) {
+            continue;
+        }
+        let mut battery = cmds.entity(e);
+        battery.remove::<(HeldBattery, TakenFrom)>().insert((
+            taken_from.transform,
+            Collider::cylinder(1.0, 2.0),
+            Sensor,
+            Visibility::Inherited,
+        ));
+        match taken_from.parent {
+            Some(parent) => battery.set_parent(parent),
+            None => battery.remove_parent(),
+        };
+        if taken_from.on_terrain {
+            battery.insert(OnTerrain);
+        }
+        set_casts_shadows(&mut cmds, &q_children, e, true);
+    }
+
+    for (i, &e) in inventory.batteries.iter().rev().enumerate() {
+        let offset = HELD_OFFSET + Vec3::NEG_X * HELD_SPACING * i as f32;
+        cmds.entity(e)
+            .remove::<(Collider, RigidBody, Sensor, OnTerrain)>()
+            .insert((
+                HeldBattery,
+                Transform::from_translation(offset)
+                    .with_rotation(Quat::from_rotation_x(0.2))
+                    .with_scale(Vec3::splat(HELD_SCALE)),
+                Visibility::Inherited,
+            ))
+            .set_parent(camera_e);
+        set_casts_shadows(&mut cmds, &q_children, e, false);
+    }
+}
+
+/// Every battery carried slows the player down
+pub fn carry_weight(
+    mut q_player: Query<(&Inventory, &mut Speed), (With<Player>, Changed<Inventory>)>,
+) {
+    for (inventory, mut speed) in &mut q_player {
+        speed.0 = PLAYER_SPEED * (1.0 - SPEED_PENALTY * inventory.batteries.len() as f32);
+    }
+}
+
+/// Throws the last battery taken in front of the player, it can be taken again
+pub fn drop_battery(
+    mut cmds: Commands,
+    q_camera: Query<&GlobalTransform, With<MainCamera>>,
+    mut q_inventory: Query<&mut Inventory>,
+    q_children: Query<&Children>,
+) {
+    let (Ok(cam_tr), Ok(mut inventory)) = (q_camera.get_single(), q_inventory.get_single_mut())
+    else {
+        return;
+    };
+    let Some(battery_e) = inventory.batteries.pop() else {
+        return;
+    };
+    let forward = cam_tr.forward();
+    cmds.entity(battery_e)
+        .remove::<HeldBattery>()
+        .remove_parent()
+        .insert((
+            Transform::from_translation(cam_tr.translation() + forward * DROP_DISTANCE),
+            RigidBody::Dynamic,
+            Collider::cylinder(1.0, 2.0),
+            LinearVelocity(forward * DROP_SPEED),
+        ));
+    set_casts_shadows(&mut cmds, &q_children, battery_e, true);
+}
@@ -1,36 +1,38 @@
-use avian3d::{
-    prelude::{Collider, Sensor},
-    spatial_query::{SpatialQuery, SpatialQueryFilter},
-};
+mod carry;
+mod placement;
+
 use bevy::prelude::*;
 use leafwing_input_manager::common_conditions::action_just_pressed;
 
 use crate::{
-    camera::{CameraRange, MainCamera},
     input::Action,
-    player::{Inventory, Player},
+    interaction::{Interactable, Interacted, InteractionSet},
+    player::Inventory,
+    terrain::{OnTerrain, PlacementSet},
 };
 
+pub use carry::{HeldBattery, TakenFrom, MAX_CARRIED};
+pub use placement::BatteryCount;
+
 pub struct BatteryPlugin;
 
 impl Plugin for BatteryPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Battery>()
+        app.add_plugins(placement::BatteryPlacementPlugin)
+            .register_type::<Battery>()
             .register_type::<BatterySlot>()
-            .register_type::<PointingAtBattery>()
-            .register_type::<PointingAtSlot>()
-            .insert_resource(PointingAtBattery(None))
-            .insert_resource(PointingAtSlot(None))
             .add_systems(
                 Update,
                 (
-                    raycast_batteries,
-                    (
-                        (interact_text, interact_slot_text),
-                        (take, place).run_if(action_just_pressed(Action::Interact)),
-                    ),
+                    (take, place),
+                    carry::drop_battery.run_if(action_just_pressed(Action::DropBattery)),
+                    carry::hold_batteries,
+                    carry::carry_weight,
+                    (slot_interactables, update_prompts),
                 )
-                    .chain(),
+                    .chain()
+                    .after(InteractionSet)
+                    .after(PlacementSet::Objects),
             );
     }
 }
@@ -39,172 +41,104 @@ impl Plugin for BatteryPlugin {
 #[reflect(Component)]
 pub struct Battery;
 
-#[derive(PartialEq, Resource, Reflect)]
-#[reflect(Resource)]
-pub struct PointingAtBattery(Option<Entity>);
-
-#[derive(PartialEq, Resource, Reflect)]
-#[reflect(Resource)]
-pub struct PointingAtSlot(Option<Entity>);
-
-#[derive(Component)]
-pub struct BatteryTakeText;
-
-#[derive(Component)]
-pub struct BatteryPlaceText;
-
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct BatterySlot {
     pub filled: bool,
 }
 
-fn raycast_batteries(
-    mut pointing_at_battery: ResMut<PointingAtBattery>,
-    mut pointing_at_slot: ResMut<PointingAtSlot>,
-    q_camera: Query<(&GlobalTransform, &CameraRange), With<MainCamera>>,
-    q_player: Query<(Entity, &Children), With<Player>>,
-    q_battery: Query<(), With<Battery>>,
-    q_slot: Query<&BatterySlot>,
-    spatial_query: SpatialQuery,
-    q_ignored: Query<Entity, (With<Sensor>, Without<Battery>, Without<BatterySlot>)>,
-) {
-    let Ok((player_e, player_children)) = q_player.get_single() else {
-        return;
-    };
-
-    let Ok((cam_tr, range)) = q_camera.get_single() else {
-        return;
-    };
+/// Battery placed in the slot
+#[derive(Component)]
+pub struct SlotBattery(pub Entity);
 
-    if let Some(hit) = spatial_query.cast_ray_predicate(
-        cam_tr.translation(), // Origin
-        cam_tr.forward(),     // Direction
-        range.0,              // Maximum time of impact (travel distance)
-        true,                 // Does the ray treat colliders as "solid"
-        SpatialQueryFilter::from_excluded_entities(vec![
-            player_e,
-            player_children[0],
-            player_children[1],
-        ]), // Query filter
-        &|e| !q_ignored.contains(e),
-    ) {
-        pointing_at_battery.set_if_neq(PointingAtBattery(
-            q_battery.contains(hit.entity).then_some(hit.entity),
-        ));
-        pointing_at_slot.set_if_neq(PointingAtSlot(
-            q_slot
-                .get(hit.entity)
-                .is_ok_and(|slot| !slot.filled)
-                .then_some(hit.entity),
-        ));
-    }
-}
+// Prompts shown while pointing at batteries and slots, depending on the batteries carried
+const TAKE_PROMPT: &str = "Press <interact> to take the battery.";
+const FULL_PROMPT: &str = "You can't carry any more batteries.";
+const PLACE_PROMPT: &str = "Press <interact> to place a battery.";
+const EMPTY_PROMPT: &str = "You are not carrying any batteries.";
 
-fn interact_text(
-    pointing_at: Res<PointingAtBattery>,
+/// Only empty slots can be interacted with
+fn slot_interactables(
     mut cmds: Commands,
-    asset_server: Res<AssetServer>,
-    q_text: Query<Entity, With<BatteryTakeText>>,
+    q_slots: Query<(Entity, &BatterySlot), Changed<BatterySlot>>,
 ) {
-    if pointing_at.0.is_some() && q_text.is_empty() {
-        cmds.spawn((
-            BatteryTakeText,
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::End,
-                    justify_content: JustifyContent::Center,
-                    padding: UiRect::all(Val::Percent(5.0)),
-                    ..default()
-                },
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                "Press <interact> to take the battery.",
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 30.0,
-                    color: Color::srgb(0.9, 0.9, 0.9),
-                },
-            ));
-        });
-    } else if pointing_at.0.is_none() {
-        for e in &q_text {
-            cmds.entity(e).despawn_recursive();
+    for (e, slot) in &q_slots {
+        match slot.filled {
+            true => {
+                cmds.entity(e).remove::<Interactable>();
+            }
+            false => {
+                cmds.entity(e).insert(Interactable::new(PLACE_PROMPT));
+            }
         }
     }
 }
 
-fn interact_slot_text(
-    pointing_at: Res<PointingAtSlot>,
-    mut cmds: Commands,
-    asset_server: Res<AssetServer>,
+fn update_prompts(
     q_inventory: Query<&Inventory>,
-    q_text: Query<Entity, With<BatteryPlaceText>>,
+    mut q_batteries: Query<&mut Interactable, (With<Battery>, Without<BatterySlot>)>,
+    mut q_slots: Query<&mut Interactable, With<BatterySlot>>,
 ) {
-    if pointing_at.0.is_some() && q_text.is_empty() {
-        let inventory = q_inventory.single();
-        cmds.spawn((
-            BatteryPlaceText,
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(100.0),
-                    height: Val::Percent(100.0),
-                    align_items: AlignItems::End,
-                    justify_content: JustifyContent::Center,
-                    padding: UiRect::all(Val::Percent(5.0)),
-                    ..default()
-                },
-                ..default()
-            },
-        ))
-        .with_children(|parent| {
-            parent.spawn(TextBundle::from_section(
-                if inventory.batteries.len() == 0 {
-                    "You are not carrying any batteries."
-                } else {
-                    "Press <interact> to place a battery."
-                },
-                TextStyle {
-                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    font_size: 30.0,
-                    color: Color::srgb(0.9, 0.9, 0.9),
-                },
-            ));
-        });
-    } else if pointing_at.0.is_none() {
-        for e in &q_text {
-            cmds.entity(e).despawn_recursive();
+    let Ok(inventory) = q_inventory.get_single() else {
+        return;
+    };
+    let take_prompt = match inventory.batteries.len() >= MAX_CARRIED {
+        true => FULL_PROMPT,
+        false => TAKE_PROMPT,
+    };
+    let place_prompt = match inventory.batteries.is_empty() {
+        true => EMPTY_PROMPT,
+        false => PLACE_PROMPT,
+    };
+    for mut interactable in &mut q_batteries {
+        if interactable.prompt != take_prompt {
+            interactable.prompt = take_prompt.to_string();
+        }
+    }
+    for mut interactable in &mut q_slots {
+        if interactable.prompt != place_prompt {
+            interactable.prompt = place_prompt.to_string();
         }
     }
 }
 
 fn take(
     mut cmds: Commands,
-    pointing_at: Res<PointingAtBattery>,
+    mut ev_interacted: EventReader<Interacted>,
+    q_battery: Query<(&Transform, Option<&Parent>, Has<OnTerrain>), With<Battery>>,
     mut q_inventory: Query<&mut Inventory>,
 ) {
-    if let Some(battery_e) = pointing_at.0 {
-        q_inventory.single_mut().batteries.push(battery_e);
-        cmds.entity(battery_e)
-            .insert(Visibility::Hidden)
-            .remove::<Collider>()
-            .remove_parent();
+    let Ok(mut inventory) = q_inventory.get_single_mut() else {
+        return;
+    };
+    for Interacted(battery_e) in ev_interacted.read() {
+        let Ok((tr, parent, on_terrain)) = q_battery.get(*battery_e) else {
+            continue;
+        };
+        if inventory.batteries.len() < MAX_CARRIED {
+            cmds.entity(*battery_e).insert(carry::TakenFrom {
+                parent: parent.map(Parent::get),
+                transform: *tr,
+                on_terrain,
+            });
+            // Held in front of the camera by `hold_batteries`
+            inventory.batteries.push(*battery_e);
+        }
     }
 }
 
 fn place(
-    pointing_at: Res<PointingAtSlot>,
+    mut cmds: Commands,
+    mut ev_interacted: EventReader<Interacted>,
     mut q_inventory: Query<&mut Inventory>,
     mut q_battery: Query<(&mut Transform, &mut Visibility), With<Battery>>,
     mut q_slots: Query<(&GlobalTransform, &mut BatterySlot), (With<BatterySlot>, Without<Battery>)>,
+    q_children: Query<&Children>,
 ) {
-    let Some((slot_tr, mut slot)) = pointing_at.0.and_then(|e| q_slots.get_mut(e).ok()) else {
-        info!("not pointing at slot");
+    let Some((slot_e, (slot_tr, mut slot))) = ev_interacted
+        .read()
+        .find_map(|Interacted(e)| q_slots.get_mut(*e).ok().map(|slot| (*e, slot)))
+    else {
         return;
     };
 
@@ -221,6 +155,12 @@ fn place(
     let (mut tr, mut vis) = q_battery.get_mut(battery_e).unwrap();
     tr.translation = slot_tr.translation();
     tr.rotation = slot_tr.to_scale_rotation_translation().1;
+    tr.scale = Vec3::ONE;
     *vis = Visibility::Inherited;
     slot.filled = true;
+    cmds.entity(slot_e).insert(SlotBattery(battery_e));
+    cmds.entity(battery_e)
+        .remove::<carry::HeldBattery>()
+        .remove_parent();
+    carry::set_casts_shadows(&mut cmds, &q_children, battery_e, true);
 }
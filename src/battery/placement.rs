@@ -0,0 +1,199 @@
+use std::f32::consts::TAU;
+
+use avian3d::prelude::{Collider, Sensor};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    interaction::Interactable,
+    pyramids::Pyramid,
+    shelter::Shelter,
+    terrain::{Biome, OnTerrain, PlacementSet, TerrainParams, TerrainSampler},
+    tower::TOWER_POS,
+    util::{despawn_all, poisson_disc_sampling_with},
+};
+
+use super::{Battery, TAKE_PROMPT};
+
+/// Hides batteries in pyramids, shelters and dunes from the world seed
+pub struct BatteryPlacementPlugin;
+impl Plugin for BatteryPlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BatteryCount>()
+            .init_resource::<BatteryCount>()
+            .add_systems(Update, despawn_all::<Battery>.in_set(PlacementSet::Clear))
+            .add_systems(Update, place_batteries.in_set(PlacementSet::Objects))
+            .add_systems(Update, remove_authored_batteries);
+    }
+}
+
+/// Batteries hidden in the world, filling that many slots of the tower, or all of them, wins the
+/// game
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BatteryCount(pub usize);
+
+impl Default for BatteryCount {
+    /// One per slot of the tower
+    fn default() -> Self {
+        Self(4)
+    }
+}
+
+impl BatteryCount {
+    /// Filled slots that win the game, no more than the tower has
+    pub fn needed(&self, slots: usize) -> usize {
+        self.0.min(slots)
+    }
+}
+
+/// Placed by [`place_batteries`], the ones authored in scenes are removed
+#[derive(Component)]
+struct PlacedBattery;
+
+// Where batteries sit in the scenes they are placed in, like the one authored in the pyramid
+const PYRAMID_BATTERY_OFFSET: Vec3 = Vec3::new(0.0, 1.23, 0.0);
+const SHELTER_BATTERY_OFFSET: Vec3 = Vec3::new(0.0, 0.0, -1.0);
+// Batteries in dunes stick out of the sand by a bit less than half
+const BURIED_DEPTH: f32 = 0.6;
+const MAX_BURIED_TILT: f32 = 0.4;
+const DUNE_SPOT_SPACING: f32 = 500.0;
+const DUNE_SPOT_REGION: f32 = 4000.0;
+// Batteries in dunes are kept away from pyramids, shelters and the tower
+const SCENE_CLEARANCE: f32 = 100.0;
+// Scale of the light inside the battery model
+const BATTERY_LIGHT_SCALE: f32 = 0.3736;
+
+/// Where a battery can be placed, inside a scene or buried in the ground
+struct Site {
+    scene: Option<Entity>,
+    transform: Transform,
+}
+
+/// Sorted, since scenes are spawned in no particular order and a seed should pick the same ones
+fn scene_sites<'a>(
+    scenes: impl Iterator<Item = (Entity, &'a Transform)>,
+    offset: Vec3,
+) -> Vec<Site> {
+    let mut scenes = scenes.collect::<Vec<_>>();
+    scenes.sort_by(|(_, a), (_, b)| {
+        let (a, b) = (a.translation, b.translation);
+        a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z))
+    });
+    scenes
+        .into_iter()
+        .map(|(e, _)| Site {
+            scene: Some(e),
+            transform: Transform::from_translation(offset),
+        })
+        .collect()
+}
+
+fn place_batteries(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    count: Res<BatteryCount>,
+    tp: Res<TerrainParams>,
+    terrain: Res<TerrainSampler>,
+    q_pyramids: Query<(Entity, &Transform), With<Pyramid>>,
+    q_shelters: Query<(Entity, &Transform), With<Shelter>>,
+) {
+    let mut rng = StdRng::seed_from_u64(tp.seed() as u64);
+
+    let pyramids = scene_sites(q_pyramids.iter(), PYRAMID_BATTERY_OFFSET);
+    let shelters = scene_sites(q_shelters.iter(), SHELTER_BATTERY_OFFSET);
+    let scenes = q_pyramids
+        .iter()
+        .chain(&q_shelters)
+        .map(|(_, tr)| tr.translation.xz())
+        .chain([TOWER_POS])
+        .collect::<Vec<_>>();
+    let center = Vec2::splat(DUNE_SPOT_REGION / 2.0);
+    let spots = poisson_disc_sampling_with(
+        &mut rng,
+        DUNE_SPOT_SPACING,
+        DUNE_SPOT_REGION,
+        usize::MAX,
+        vec![TOWER_POS + center],
+    );
+    let dunes = spots
+        .into_iter()
+        .map(|p| p - center)
+        .filter(|p| terrain.biome(*p) == Biome::DuneSea)
+        .filter(|p| {
+            scenes
+                .iter()
+                .all(|scene| scene.distance(*p) > SCENE_CLEARANCE)
+        })
+        .map(|p| {
+            let rotation = Quat::from_rotation_y(rng.r#gen::<f32>() * TAU)
+                * Quat::from_rotation_x(rng.gen_range(-MAX_BURIED_TILT..MAX_BURIED_TILT));
+            let pos = p.extend(terrain.height(p) - BURIED_DEPTH).xzy();
+            Site {
+                scene: None,
+                transform: Transform::from_translation(pos).with_rotation(rotation),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut sites = [pyramids, shelters, dunes];
+    for site in &mut sites {
+        site.shuffle(&mut rng);
+    }
+    // Taken from each kind of site in turn
+    let mut picked = Vec::new();
+    while picked.len() < count.0 && sites.iter().any(|site| !site.is_empty()) {
+        picked.extend(sites.iter_mut().filter_map(|site| site.pop()));
+    }
+    picked.truncate(count.0);
+    if picked.len() < count.0 {
+        warn!(
+            "only {} of {} batteries could be placed",
+            picked.len(),
+            count.0
+        );
+    }
+
+    for site in picked {
+        let mut battery = cmds.spawn((
+            Name::new("Battery"),
+            Battery,
+            PlacedBattery,
+            Interactable::new(TAKE_PROMPT),
+            Collider::cylinder(1.0, 2.0),
+            Sensor,
+            PbrBundle {
+                mesh: asset_server.load("levels/Pyramid.glb#Mesh3/Primitive0"),
+                material: asset_server.load("levels/Pyramid.glb#Material2"),
+                transform: site.transform,
+                ..default()
+            },
+        ));
+        battery.with_children(|parent| {
+            parent.spawn(PbrBundle {
+                mesh: asset_server.load("levels/Pyramid.glb#Mesh2/Primitive0"),
+                material: asset_server.load("levels/Pyramid.glb#Material1"),
+                transform: Transform::from_scale(Vec3::splat(BATTERY_LIGHT_SCALE)),
+                ..default()
+            });
+        });
+        match site.scene {
+            Some(scene) => {
+                battery.set_parent(scene);
+            }
+            None => {
+                battery.insert(OnTerrain);
+            }
+        }
+    }
+}
+
+/// The pyramid scene comes with its own battery
+fn remove_authored_batteries(
+    mut cmds: Commands,
+    q_batteries: Query<Entity, (Added<Battery>, Without<PlacedBattery>)>,
+) {
+    for e in &q_batteries {
+        cmds.entity(e).despawn_recursive();
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::{
+    input::{
+        keyboard::{Key, KeyboardInput},
+        ButtonState,
+    },
+    prelude::*,
+};
+use leafwing_input_manager::{
+    action_state::ActionState, common_conditions::action_just_pressed, plugin::ToggleActions,
+};
+
+use crate::{
+    input::Action,
+    interaction::{InteractionSet, PointingAt},
+    menu::{
+        navigation::MenuAction,
+        styling::{bottom_root, default_text},
+        MenuState, MenuToggleSet,
+    },
+};
+
+use super::{BeaconMarking, BeaconRetraction};
+
+/// Typing a label for the beacon the player points at
+pub struct BeaconLabelPlugin;
+impl Plugin for BeaconLabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            edit_label
+                .run_if(
+                    in_state(MenuState::None).and_then(action_just_pressed(Action::LabelBeacon)),
+                )
+                .after(InteractionSet),
+        )
+        .add_systems(
+            Update,
+            (
+                type_label,
+                (close_editor, update_editor).run_if(in_state(MenuState::BeaconLabel)),
+            )
+                .chain()
+                .after(MenuToggleSet),
+        )
+        .add_systems(
+            OnEnter(MenuState::BeaconLabel),
+            (disable_actions, spawn_editor),
+        )
+        .add_systems(
+            OnExit(MenuState::BeaconLabel),
+            (enable_actions, despawn_editor),
+        );
+    }
+}
+
+const MAX_LABEL_LENGTH: usize = 16;
+
+/// Label being typed and the beacon it goes to
+#[derive(Resource)]
+struct LabelDraft {
+    beacon: Entity,
+    text: String,
+}
+
+#[derive(Component)]
+struct LabelEditor;
+
+#[derive(Component)]
+struct LabelText;
+
+fn edit_label(
+    mut cmds: Commands,
+    pointing_at: Res<PointingAt>,
+    q_markings: Query<&BeaconMarking, Without<BeaconRetraction>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    let Some(beacon) = pointing_at.0 else {
+        return;
+    };
+    let Ok(marking) = q_markings.get(beacon) else {
+        return;
+    };
+    cmds.insert_resource(LabelDraft {
+        beacon,
+        text: marking.label.clone().unwrap_or_default(),
+    });
+    menu_state.set(MenuState::BeaconLabel);
+}
+
+/// Gameplay keys are typed in the label instead
+fn disable_actions(mut toggle_actions: ResMut<ToggleActions<Action>>) {
+    toggle_actions.enabled = false;
+}
+
+fn enable_actions(mut toggle_actions: ResMut<ToggleActions<Action>>) {
+    toggle_actions.enabled = true;
+}
+
+/// Keeps reading key presses outside of the editor, so the one that opened it isn't typed
+fn type_label(
+    mut ev_keys: EventReader<KeyboardInput>,
+    menu_state: Res<State<MenuState>>,
+    mut draft: Option<ResMut<LabelDraft>>,
+) {
+    let Some(draft) = draft
+        .as_mut()
+        .filter(|_| *menu_state == MenuState::BeaconLabel)
+    else {
+        ev_keys.clear();
+        return;
+    };
+    for ev in ev_keys.read() {
+        if ev.state != ButtonState::Pressed {
+            continue;
+        }
+        match &ev.logical_key {
+            Key::Backspace => {
+                draft.text.pop();
+            }
+            Key::Space if draft.text.chars().count() < MAX_LABEL_LENGTH => {
+                draft.text.push(' ');
+            }
+            Key::Character(chars) => {
+                for c in chars.chars().filter(|c| !c.is_control()) {
+                    if draft.text.chars().count() < MAX_LABEL_LENGTH {
+                        draft.text.push(c);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Confirming keeps the label, an empty one removes it
+fn close_editor(
+    mut cmds: Commands,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut next_state: ResMut<NextState<MenuState>>,
+    draft: Option<Res<LabelDraft>>,
+    mut q_markings: Query<&mut BeaconMarking>,
+) {
+    let Some(draft) = draft else {
+        next_state.set(MenuState::None);
+        return;
+    };
+    let confirmed = menu_actions.just_pressed(&MenuAction::Confirm);
+    if !confirmed
+        && !menu_actions.just_pressed(&MenuAction::Back)
+        && !menu_actions.just_pressed(&MenuAction::Toggle)
+    {
+        return;
+    }
+    if confirmed {
+        if let Ok(mut marking) = q_markings.get_mut(draft.beacon) {
+            let label = draft.text.trim();
+            marking.label = (!label.is_empty()).then(|| label.to_string());
+        }
+    }
+    cmds.remove_resource::<LabelDraft>();
+    next_state.set(MenuState::None);
+}
+
+fn spawn_editor(mut cmds: Commands, asset_server: Res<AssetServer>) {
+    cmds.spawn((Name::new("Beacon label"), LabelEditor, bottom_root()))
+        .with_children(|parent| {
+            parent.spawn(default_text("Beacon label", 24.0, &asset_server));
+            parent.spawn((LabelText, default_text("", 48.0, &asset_server)));
+            parent.spawn(default_text(
+                "Enter to confirm, Escape to cancel",
+                20.0,
+                &asset_server,
+            ));
+        });
+}
+
+fn update_editor(draft: Option<Res<LabelDraft>>, mut q_text: Query<&mut Text, With<LabelText>>) {
+    let Some(draft) = draft else {
+        return;
+    };
+    for mut text in &mut q_text {
+        let value = format!("{}_", draft.text);
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+fn despawn_editor(mut cmds: Commands, q_editor: Query<Entity, With<LabelEditor>>) {
+    for e in &q_editor {
+        cmds.entity(e).despawn_recursive();
+    }
+}
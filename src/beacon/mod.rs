@@ -1,29 +1,201 @@
-use bevy::prelude::*;
+mod label;
 
-use crate::util::spatial_playback_remove;
+use std::f32::consts::TAU;
+
+use avian3d::prelude::{Collider, Sensor};
+use bevy::{pbr::NotShadowCaster, prelude::*};
+use leafwing_input_manager::common_conditions::action_just_pressed;
+
+use crate::{
+    camera::MainCamera,
+    input::Action,
+    interaction::{Interactable, Interacted, InteractionSet, PointingAt},
+    map::MapJournal,
+    materials::{
+        beam::{beam_bundle, BeamMaterial, BeamMesh},
+        glow::GlowMaterial,
+    },
+    player::BeaconCount,
+    sandstorm::SandstormIntensity,
+    terrain::PlacementSet,
+    tween::{Ease, Timeline, TimelineCue, Track, TweenSet, TweenTranslation},
+    util::{despawn_all, spatial_playback_remove},
+};
 
 pub struct BeaconPlugin;
 impl Plugin for BeaconPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup_models)
-            .add_systems(Update, (beacon_spawn, deploy));
+        app.add_plugins(label::BeaconLabelPlugin)
+            .register_type::<BeaconMarking>()
+            .register_type::<BeaconColor>()
+            .register_type::<BeaconDeployment>()
+            .init_resource::<LastBeaconColor>()
+            .init_resource::<BeaconDeployment>()
+            .add_systems(Startup, setup_models)
+            .add_systems(Update, despawn_all::<Beacon>.in_set(PlacementSet::Clear))
+            .add_systems(
+                Update,
+                (
+                    (beacon_spawn, tint_beacons).chain(),
+                    (pulse_beacons, scale_glows),
+                    (light_up, finish_retraction).after(TweenSet),
+                    (
+                        retract,
+                        recolor.run_if(action_just_pressed(Action::BeaconColor)),
+                    )
+                        .after(InteractionSet),
+                ),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Beacon;
 
+/// Color of the light at the top of a beacon, also used for it on the compass and map
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Reflect)]
+pub enum BeaconColor {
+    #[default]
+    Orange,
+    Red,
+    Green,
+    Blue,
+    Violet,
+}
+
+impl BeaconColor {
+    const ALL: [BeaconColor; 5] = [
+        BeaconColor::Orange,
+        BeaconColor::Red,
+        BeaconColor::Green,
+        BeaconColor::Blue,
+        BeaconColor::Violet,
+    ];
+
+    pub fn color(self) -> Color {
+        match self {
+            BeaconColor::Orange => Color::srgb(1.0, 0.45, 0.2),
+            BeaconColor::Red => Color::srgb(1.0, 0.2, 0.2),
+            BeaconColor::Green => Color::srgb(0.3, 1.0, 0.3),
+            BeaconColor::Blue => Color::srgb(0.3, 0.5, 1.0),
+            BeaconColor::Violet => Color::srgb(0.8, 0.35, 1.0),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        Self::ALL[(self as usize + 1) % Self::ALL.len()]
+    }
+}
+
+/// How the player marked a beacon, kept in checkpoints
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+pub struct BeaconMarking {
+    pub color: BeaconColor,
+    /// Shown next to the beacon on the compass and map
+    pub label: Option<String>,
+}
+
+/// Beacons are planted with the color chosen last
+#[derive(Resource, Default)]
+struct LastBeaconColor(BeaconColor);
+
+/// How beacons unfold, segments rise one after the other and the top lights up after the last
+/// one. Beacons already planted keep the timeline they were planted with.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BeaconDeployment {
+    pub segment_count: usize,
+    pub segment_height: f32,
+    /// Seconds each segment takes to rise
+    pub segment_time: f32,
+}
+
+impl Default for BeaconDeployment {
+    fn default() -> Self {
+        Self {
+            segment_count: 15,
+            segment_height: 2.19,
+            segment_time: 0.5,
+        }
+    }
+}
+
+impl BeaconDeployment {
+    /// Seconds from planting to the top lighting up
+    fn duration(&self) -> f32 {
+        (self.segment_count + 1) as f32 * self.segment_time
+    }
+
+    /// Segment tracks and sounds are added to it as the segments are spawned
+    fn timeline(&self) -> Timeline {
+        Timeline::new(self.duration()).with_event(self.duration(), LIT_CUE)
+    }
+
+    /// Seconds at which segment `i` clicks into place
+    fn segment_end(&self, i: usize) -> f32 {
+        (i + 1) as f32 * self.segment_time
+    }
+
+    /// Rise of segment `i` from the top of the one below it
+    fn segment_track(&self, i: usize) -> Track<Vec3> {
+        Track::new(Vec3::ZERO)
+            .hold(i as f32 * self.segment_time)
+            .to(
+                self.segment_end(i),
+                Vec3::Y * self.segment_height,
+                Ease::QuartIn,
+            )
+    }
+}
+
+/// Folding back, the beacon is picked up once its timeline is back to the start
+#[derive(Component)]
+struct BeaconRetraction;
+
 #[derive(Component, Reflect)]
 pub struct BeaconParts {
     segments: Vec<Entity>,
     top_off: Entity,
     top_on: Entity,
+    light: Entity,
+    beam: Entity,
+    glow: Entity,
 }
 
+/// Halo around the lit top, seen through the storm
 #[derive(Component)]
-pub struct BeaconTimestamp(f32);
+struct BeaconGlow;
+
+const LIT_CUE: &str = "lit";
+// Size of the collider around the bottom of the beacon, before its scale
+const BASE_RADIUS: f32 = 0.5;
+const BASE_HEIGHT: f32 = 2.0;
+const BEACON_SCALE: f32 = 2.0;
+
+// Shines up from the lit top, hidden with it, before the beacon is scaled up
+const BEAM_BRIGHTNESS: f32 = 5.0;
+const BEAM_ALPHA: f32 = 0.5;
+const BEAM_BASE: f32 = 1.0;
+const BEAM_RADIUS: f32 = 0.25;
+const BEAM_HEIGHT: f32 = 100.0;
+const LIGHT_HEIGHT: f32 = 0.5;
+const LIGHT_INTENSITY: f32 = 100_000.0;
 
-const SEGMENT_HEIGHT: f32 = 2.19;
+// Lit beacons dim down to this fraction of their brightness and back, all together
+const PULSE_PERIOD: f32 = 2.0;
+const PULSE_LOW: f32 = 0.4;
+const GLOW_BRIGHTNESS: f32 = 20.0;
+// Halos keep about the same size on screen, and fade out toward their range
+const GLOW_ANGULAR_SIZE: f32 = 0.004;
+const GLOW_MIN_RADIUS: f32 = 0.8;
+const GLOW_FADE: f32 = 0.25;
+// Range of the halos in clear weather and in the strongest storm, the camera sees up to 1 km
+const GLOW_CLEAR_RANGE: f32 = 950.0;
+const GLOW_STORM_RANGE: f32 = 600.0;
+
+const BEACON_PROMPT: &str =
+    "Press <interact> to retract the beacon, <beacon color> to recolor it or <label beacon> to label it.";
 
 #[derive(Resource)]
 pub struct BeaconAssets {
@@ -34,9 +206,30 @@ pub struct BeaconAssets {
     sfx_plant: Handle<AudioSource>,
     sfx_segment: Handle<AudioSource>,
     sfx_light: Handle<AudioSource>,
+    /// One for each [`BeaconColor`]
+    beam_materials: Vec<Handle<BeamMaterial>>,
+    glow_mesh: Handle<Mesh>,
+    /// One for each [`BeaconColor`], pulsing
+    glow_materials: Vec<Handle<GlowMaterial>>,
 }
 
-fn setup_models(mut cmds: Commands, asset_server: Res<AssetServer>) {
+impl BeaconAssets {
+    fn beam_material(&self, color: BeaconColor) -> Handle<BeamMaterial> {
+        self.beam_materials[color as usize].clone()
+    }
+
+    fn glow_material(&self, color: BeaconColor) -> Handle<GlowMaterial> {
+        self.glow_materials[color as usize].clone()
+    }
+}
+
+fn setup_models(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut beam_materials: ResMut<Assets<BeamMaterial>>,
+    mut glow_materials: ResMut<Assets<GlowMaterial>>,
+) {
     cmds.insert_resource(BeaconAssets {
         model_bottom: asset_server.load("models/beacon/bottom.glb#Scene0"),
         model_segment: asset_server.load("models/beacon/segment.glb#Scene0"),
@@ -45,28 +238,81 @@ fn setup_models(mut cmds: Commands, asset_server: Res<AssetServer>) {
         sfx_plant: asset_server.load("audio/sfx/beacon_plant.ogg"),
         sfx_segment: asset_server.load("audio/sfx/beacon_segment.ogg"),
         sfx_light: asset_server.load("audio/sfx/beacon_light.ogg"),
+        beam_materials: BeaconColor::ALL
+            .iter()
+            .map(|color| {
+                let beam = LinearRgba::from(color.color()) * BEAM_BRIGHTNESS;
+                beam_materials.add(BeamMaterial {
+                    color: beam.with_alpha(BEAM_ALPHA),
+                })
+            })
+            .collect(),
+        glow_mesh: meshes.add(Sphere::new(1.0).mesh().uv(16, 8)),
+        glow_materials: BeaconColor::ALL
+            .iter()
+            .map(|color| {
+                glow_materials.add(GlowMaterial {
+                    color: glow_color(*color, 1.0),
+                })
+            })
+            .collect(),
     });
 }
 
+fn glow_color(color: BeaconColor, pulse: f32) -> LinearRgba {
+    (LinearRgba::from(color.color()) * GLOW_BRIGHTNESS * pulse).with_alpha(1.0)
+}
+
+/// Brightness of lit beacons, from [`PULSE_LOW`] to 1
+fn pulse(time: f32) -> f32 {
+    let wave = 0.5 + 0.5 * (time * TAU / PULSE_PERIOD).cos();
+    PULSE_LOW + (1.0 - PULSE_LOW) * wave
+}
+
+/// Beyond it halos are hidden, it shrinks as the storm gets stronger
+pub fn glow_range(intensity: f32) -> f32 {
+    GLOW_CLEAR_RANGE + (GLOW_STORM_RANGE - GLOW_CLEAR_RANGE) * intensity.clamp(0.0, 1.0)
+}
+
 const BEACON_SOUND_VOLUME: f32 = 3.0;
 
 fn beacon_spawn(
     mut cmds: Commands,
-    time: Res<Time>,
     q_added_beacons: Query<(Entity, &Transform), Added<Beacon>>,
     assets: Res<BeaconAssets>,
+    beam_mesh: Res<BeamMesh>,
+    last_color: Res<LastBeaconColor>,
+    deployment: Res<BeaconDeployment>,
 ) {
     for (e, transform) in &q_added_beacons {
+        let marking = BeaconMarking {
+            color: last_color.0,
+            label: None,
+        };
+        let mut timeline = deployment.timeline();
         let mut anchor = e;
         let mut segments = vec![];
-        for _ in 0..15 {
+        for i in 0..deployment.segment_count {
             let segment = cmds
-                .spawn(SceneBundle {
-                    scene: assets.model_segment.clone(),
-                    ..default()
-                })
+                .spawn((
+                    SceneBundle {
+                        scene: assets.model_segment.clone(),
+                        ..default()
+                    },
+                    TweenTranslation {
+                        timeline: e,
+                        track: deployment.segment_track(i),
+                    },
+                ))
                 .set_parent(anchor)
                 .id();
+            // Segments click into place both ways
+            timeline = timeline.with_sound(
+                deployment.segment_end(i),
+                segment,
+                assets.sfx_segment.clone(),
+                spatial_playback_remove(BEACON_SOUND_VOLUME, 0.4),
+            );
             segments.push(segment);
             anchor = segment;
         }
@@ -77,12 +323,46 @@ fn beacon_spawn(
             })
             .set_parent(anchor)
             .id();
+        let light = cmds
+            .spawn(PointLightBundle {
+                point_light: PointLight {
+                    color: marking.color.color(),
+                    intensity: LIGHT_INTENSITY,
+                    range: 15.0,
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, LIGHT_HEIGHT, 0.0),
+                ..default()
+            })
+            .id();
+        let beam = cmds
+            .spawn(beam_bundle(
+                &beam_mesh,
+                assets.beam_material(marking.color),
+                Vec3::Y * BEAM_BASE,
+                BEAM_RADIUS,
+                BEAM_HEIGHT,
+            ))
+            .id();
+        let glow = cmds
+            .spawn((
+                BeaconGlow,
+                MaterialMeshBundle {
+                    mesh: assets.glow_mesh.clone(),
+                    material: assets.glow_material(marking.color),
+                    transform: Transform::from_xyz(0.0, LIGHT_HEIGHT, 0.0),
+                    ..default()
+                },
+                NotShadowCaster,
+            ))
+            .id();
         let top_on = cmds
             .spawn(SceneBundle {
                 scene: assets.model_top_on.clone(),
                 visibility: Visibility::Hidden,
                 ..default()
             })
+            .push_children(&[light, beam, glow])
             .set_parent(anchor)
             .id();
         cmds.entity(e)
@@ -91,8 +371,14 @@ fn beacon_spawn(
                     segments,
                     top_off,
                     top_on,
+                    light,
+                    beam,
+                    glow,
                 },
-                BeaconTimestamp(time.elapsed_seconds()),
+                marking,
+                timeline,
+                Collider::cylinder(BASE_RADIUS, BASE_HEIGHT),
+                Sensor,
                 SceneBundle {
                     scene: assets.model_bottom.clone(),
                     ..default()
@@ -103,43 +389,161 @@ fn beacon_spawn(
                 },
             ))
             .insert(
-                Transform::from_xyz(0.0, 0.5, 0.0) * transform.clone().with_scale(Vec3::splat(2.0)),
+                Transform::from_xyz(0.0, 0.5, 0.0)
+                    * transform.clone().with_scale(Vec3::splat(BEACON_SCALE)),
             );
     }
 }
 
-fn deploy(
-    time: Res<Time>,
+/// Lights up once deployed, and goes out as soon as it is retracted
+fn light_up(
     mut cmds: Commands,
-    q_beacons: Query<(&BeaconParts, &BeaconTimestamp)>,
-    mut q_segment: Query<&mut Transform>,
-    mut q_end: Query<&mut Visibility>,
-    mut prev_time: Local<f32>,
+    mut ev_cue: EventReader<TimelineCue>,
+    q_beacons: Query<&BeaconParts>,
+    mut q_visibility: Query<&mut Visibility>,
     assets: Res<BeaconAssets>,
 ) {
-    for (parts, timestamp) in &q_beacons {
-        let t = (time.elapsed_seconds() - timestamp.0) * 2.0;
-        let prev_t = (*prev_time - timestamp.0) * 2.0;
-        for (i, segment_e) in parts.segments.iter().enumerate() {
-            let mut segment_tr = q_segment.get_mut(*segment_e).unwrap();
-            if (t - i as f32) > 1.0 && (prev_t - i as f32) <= 1.0 {
-                cmds.entity(*segment_e).insert(AudioBundle {
-                    source: assets.sfx_segment.clone(),
-                    settings: spatial_playback_remove(BEACON_SOUND_VOLUME, 0.4),
+    for cue in ev_cue.read().filter(|cue| cue.name == LIT_CUE) {
+        let Ok(parts) = q_beacons.get(cue.timeline) else {
+            continue;
+        };
+        let Ok([mut top_off, mut top_on]) =
+            q_visibility.get_many_mut([parts.top_off, parts.top_on])
+        else {
+            continue;
+        };
+        match cue.forward {
+            true => {
+                (*top_off, *top_on) = (Visibility::Hidden, Visibility::Inherited);
+                cmds.entity(parts.top_on).insert(AudioBundle {
+                    source: assets.sfx_light.clone(),
+                    settings: spatial_playback_remove(BEACON_SOUND_VOLUME * 14.0, 0.1),
                 });
+                // Can be marked and retracted once fully deployed
+                cmds.entity(cue.timeline)
+                    .insert(Interactable::new(BEACON_PROMPT));
+            }
+            false => {
+                (*top_off, *top_on) = (Visibility::Inherited, Visibility::Hidden);
             }
-            let t = (t - i as f32).clamp(0.0, 1.0).powf(4.0);
-            segment_tr.translation.y = t * SEGMENT_HEIGHT;
         }
-        let end_t = parts.segments.len() as f32 + 1.0;
-        if t > end_t && prev_t <= end_t {
-            *q_end.get_mut(parts.top_off).unwrap() = Visibility::Hidden;
-            *q_end.get_mut(parts.top_on).unwrap() = Visibility::Visible;
-            cmds.entity(parts.top_on).insert(AudioBundle {
-                source: assets.sfx_light.clone(),
-                settings: spatial_playback_remove(BEACON_SOUND_VOLUME * 14.0, 0.1),
-            });
+    }
+}
+
+/// The beacon plays its deployment backward
+fn retract(
+    mut cmds: Commands,
+    mut ev_interacted: EventReader<Interacted>,
+    mut q_beacons: Query<&mut Timeline, (With<Beacon>, Without<BeaconRetraction>)>,
+    assets: Res<BeaconAssets>,
+) {
+    for Interacted(e) in ev_interacted.read() {
+        let Ok(mut timeline) = q_beacons.get_mut(*e) else {
+            continue;
+        };
+        timeline.speed = -1.0;
+        cmds.entity(*e).remove::<Interactable>().insert((
+            BeaconRetraction,
+            AudioBundle {
+                source: assets.sfx_plant.clone(),
+                settings: spatial_playback_remove(BEACON_SOUND_VOLUME, 0.4),
+            },
+        ));
+    }
+}
+
+/// Folded beacons go back to the player and leave the map
+fn finish_retraction(
+    mut cmds: Commands,
+    q_beacons: Query<(Entity, &Timeline), With<BeaconRetraction>>,
+    mut q_count: Query<&mut BeaconCount>,
+    mut journal: ResMut<MapJournal>,
+) {
+    for (e, timeline) in &q_beacons {
+        if !timeline.finished() {
+            continue;
+        }
+        cmds.entity(e).despawn_recursive();
+        for mut count in &mut q_count {
+            count.0 += 1;
+        }
+        journal.landmarks.retain(|landmark| landmark.entity != e);
+    }
+}
+
+/// Cycles the color of the beacon the player points at
+fn recolor(
+    pointing_at: Res<PointingAt>,
+    mut q_markings: Query<&mut BeaconMarking, Without<BeaconRetraction>>,
+    mut last_color: ResMut<LastBeaconColor>,
+) {
+    let Some(mut marking) = pointing_at.0.and_then(|e| q_markings.get_mut(e).ok()) else {
+        return;
+    };
+    marking.color = marking.color.next();
+    last_color.0 = marking.color;
+}
+
+/// Also restores the colors kept in a checkpoint
+fn tint_beacons(
+    q_beacons: Query<(&BeaconParts, &BeaconMarking), Changed<BeaconMarking>>,
+    mut q_lights: Query<&mut PointLight>,
+    mut q_beams: Query<&mut Handle<BeamMaterial>>,
+    mut q_glows: Query<&mut Handle<GlowMaterial>>,
+    assets: Res<BeaconAssets>,
+) {
+    for (parts, marking) in &q_beacons {
+        if let Ok(mut light) = q_lights.get_mut(parts.light) {
+            light.color = marking.color.color();
+        }
+        if let Ok(mut beam) = q_beams.get_mut(parts.beam) {
+            *beam = assets.beam_material(marking.color);
+        }
+        if let Ok(mut glow) = q_glows.get_mut(parts.glow) {
+            *glow = assets.glow_material(marking.color);
+        }
+    }
+}
+
+fn pulse_beacons(
+    time: Res<Time>,
+    assets: Res<BeaconAssets>,
+    mut glow_materials: ResMut<Assets<GlowMaterial>>,
+    q_beacons: Query<&BeaconParts>,
+    mut q_lights: Query<&mut PointLight>,
+) {
+    let brightness = pulse(time.elapsed_seconds());
+    for (color, handle) in BeaconColor::ALL.iter().zip(&assets.glow_materials) {
+        if let Some(material) = glow_materials.get_mut(handle) {
+            material.color = glow_color(*color, brightness);
+        }
+    }
+    for parts in &q_beacons {
+        if let Ok(mut light) = q_lights.get_mut(parts.light) {
+            light.intensity = LIGHT_INTENSITY * brightness;
+        }
+    }
+}
+
+/// Fog doesn't apply to halos, they fade out with the distance instead
+fn scale_glows(
+    q_camera: Query<&GlobalTransform, With<MainCamera>>,
+    intensity: Res<SandstormIntensity>,
+    mut q_glows: Query<(&mut Transform, &mut Visibility, &GlobalTransform), With<BeaconGlow>>,
+) {
+    let Ok(camera_tr) = q_camera.get_single() else {
+        return;
+    };
+    let range = glow_range(intensity.0);
+    for (mut tr, mut visibility, glow_tr) in &mut q_glows {
+        let dist = glow_tr.translation().distance(camera_tr.translation());
+        let fade = ((range - dist) / (range * GLOW_FADE)).clamp(0.0, 1.0);
+        if fade <= 0.0 {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
         }
+        visibility.set_if_neq(Visibility::Inherited);
+        let radius = (dist * GLOW_ANGULAR_SIZE).max(GLOW_MIN_RADIUS) * fade;
+        tr.scale = Vec3::splat(radius / BEACON_SCALE);
     }
-    *prev_time = time.elapsed_seconds();
 }
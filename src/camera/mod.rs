@@ -2,6 +2,8 @@ use bevy::{self, prelude::*};
 use flycam::{FlyCam, FlycamPlugin};
 use follow::{FollowCameraPlugin, IsControlled};
 
+use crate::settings::ViewDistance;
+
 pub mod flycam;
 pub mod follow;
 mod spawn;
@@ -13,7 +15,7 @@ impl Plugin for CameraPlugin {
             .register_type::<CameraMode>()
             .insert_resource(CameraMode::Free)
             .add_systems(Startup, spawn::setup_normal)
-            .add_systems(Update, apply_mode);
+            .add_systems(Update, (apply_mode, apply_draw_distance));
 
         #[cfg(feature = "dev")]
         {
@@ -56,6 +58,21 @@ pub enum CameraMode {
 #[derive(Component)]
 pub struct MainCamera;
 
+fn apply_draw_distance(
+    view_distance: Res<ViewDistance>,
+    mut q_projection: Query<&mut Projection, With<MainCamera>>,
+    q_added: Query<(), Added<MainCamera>>,
+) {
+    if !view_distance.is_changed() && q_added.is_empty() {
+        return;
+    }
+    for mut projection in &mut q_projection {
+        if let Projection::Perspective(perspective) = &mut *projection {
+            perspective.far = view_distance.draw_distance();
+        }
+    }
+}
+
 #[derive(Component)]
 pub struct CameraRange(pub f32);
 
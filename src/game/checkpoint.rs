@@ -1,6 +1,17 @@
+use std::sync::Arc;
+
 use bevy::prelude::*;
 
-use crate::player::{Inventory, Player};
+use crate::{
+    battery::{Battery, BatterySlot, HeldBattery, SlotBattery, TakenFrom},
+    beacon::{Beacon, BeaconMarking},
+    map::{LandmarkKind, MapFog, MapJournal},
+    player::{Inventory, Player},
+    sandstorm::SandDrifts,
+    terrain::{EditLayer, TerrainEdits, TerrainParams},
+};
+
+use super::{inhibitor::InhibitorProgress, monolith::Sigils};
 
 pub struct CheckpointPlugin;
 impl Plugin for CheckpointPlugin {
@@ -14,6 +25,15 @@ impl Plugin for CheckpointPlugin {
 pub struct Checkpoint {
     pos: Vec3,
     inventory: Inventory,
+    map: MapJournal,
+    fog: MapFog,
+    sigils: Sigils,
+    beacons: Vec<(Entity, BeaconMarking)>,
+    #[reflect(ignore)]
+    edits: Arc<EditLayer>,
+    #[reflect(ignore)]
+    drifts: SandDrifts,
+    dune_cycles: u32,
 }
 
 pub fn save_checkpoint(world: &mut World) {
@@ -23,8 +43,28 @@ pub fn save_checkpoint(world: &mut World) {
     let checkpoint = Checkpoint {
         pos: transform.translation,
         inventory: inventory.clone(),
+        map: world.resource::<MapJournal>().clone(),
+        fog: world.resource::<MapFog>().clone(),
+        sigils: world.resource::<Sigils>().clone(),
+        beacons: world
+            .query::<(Entity, &BeaconMarking)>()
+            .iter(world)
+            .map(|(e, marking)| (e, marking.clone()))
+            .collect(),
+        edits: world.resource::<TerrainEdits>().layer(),
+        drifts: world.resource::<SandDrifts>().clone(),
+        dune_cycles: world.resource::<TerrainParams>().dune_cycles(),
     };
     world.insert_resource(checkpoint);
+
+    // Only batteries taken after this checkpoint are put back when it is loaded
+    let taken = world
+        .query_filtered::<Entity, (With<Battery>, With<TakenFrom>)>()
+        .iter(world)
+        .collect::<Vec<_>>();
+    for e in taken {
+        world.entity_mut(e).remove::<TakenFrom>();
+    }
 }
 
 pub fn load_checkpoint(world: &mut World) {
@@ -34,5 +74,57 @@ pub fn load_checkpoint(world: &mut World) {
             .single_mut(world);
         transform.translation = checkpoint.pos;
         *inventory = checkpoint.inventory.clone();
+        let mut journal = checkpoint.map.clone();
+        // Beacons retracted since then stay gone
+        journal.landmarks.retain(|landmark| {
+            landmark.kind != LandmarkKind::Beacon || world.get::<Beacon>(landmark.entity).is_some()
+        });
+        world.insert_resource(journal);
+        world.insert_resource(checkpoint.fog.clone());
+        // Monoliths are synced with it by `sync_monoliths`
+        world.insert_resource(checkpoint.sigils.clone());
+        // Sand drifts and other edits made since then are undone
+        world
+            .resource_mut::<TerrainEdits>()
+            .restore(checkpoint.edits.clone());
+        world.insert_resource(checkpoint.drifts.clone());
+        // Changing the params rebuilds the whole terrain, only do it if the dunes moved
+        let mut tp = world.resource_mut::<TerrainParams>();
+        if tp.dune_cycles() != checkpoint.dune_cycles {
+            tp.set_dune_cycles(checkpoint.dune_cycles);
+        }
+        // Beacons marked since then get their colors and labels back
+        for (e, marking) in &checkpoint.beacons {
+            if let Some(mut current) = world.get_mut::<BeaconMarking>(*e) {
+                *current = marking.clone();
+            }
+        }
+
+        // Batteries placed since then are back in the inventory, or back where they were taken
+        // from by `hold_batteries` if they were taken since then too
+        let placed = world
+            .query::<(Entity, &SlotBattery)>()
+            .iter(world)
+            .map(|(e, battery)| (e, battery.0))
+            .collect::<Vec<_>>();
+        for (slot_e, battery_e) in placed {
+            let taken = world.get::<TakenFrom>(battery_e).is_some();
+            if !taken && !checkpoint.inventory.batteries.contains(&battery_e) {
+                continue;
+            }
+            if let Some(mut slot) = world.get_mut::<BatterySlot>(slot_e) {
+                slot.filled = false;
+            }
+            world.entity_mut(slot_e).remove::<SlotBattery>();
+            if taken {
+                world.entity_mut(battery_e).insert(HeldBattery);
+            }
+        }
+        // Stages powered since then are off again before the next cycle starts
+        let mut q_slots = world.query::<&BatterySlot>();
+        let progress = InhibitorProgress::count(q_slots.iter(world));
+        world
+            .resource_mut::<InhibitorProgress>()
+            .set_if_neq(progress);
     });
 }
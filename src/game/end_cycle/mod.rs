@@ -3,38 +3,46 @@ use bevy::prelude::*;
 use crate::{
     menu::styling::{self, default_text, PADDING},
     sandstorm::SandstormIntensity,
+    terrain::TerrainParams,
+    tween::{Ease, Timeline, TimelineCue, Track, TweenBackground, TweenSet},
 };
 
 use super::GameState;
 
 const END_CYCLE_DURATION: f32 = 5.0;
+const END_CUE: &str = "end";
 
 pub struct EndCyclePlugin;
 impl Plugin for EndCyclePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::EndCycle), setup_intro)
+        app.add_systems(OnEnter(GameState::EndCycle), (setup_intro, migrate_dunes))
             .add_systems(
                 Update,
-                (update_end_cycle, update_root_visibility).run_if(in_state(GameState::EndCycle)),
+                end_cycle
+                    .after(TweenSet)
+                    .run_if(in_state(GameState::EndCycle)),
             );
     }
 }
 
-#[derive(Resource)]
-pub struct EndCycleTime(f32);
-
 #[derive(Component)]
 pub struct EndCycleRoot;
 
+/// The black screen fades out, back to the next cycle
 fn setup_intro(mut cmds: Commands, asset_server: Res<AssetServer>) {
-    cmds.insert_resource(EndCycleTime(0.0));
     cmds.insert_resource(SandstormIntensity(0.0));
-    cmds.spawn((
+    let mut root = cmds.spawn((
         styling::opaque_root(),
         StateScoped(GameState::EndCycle),
         EndCycleRoot,
-    ))
-    .with_children(|cmds| {
+        Timeline::new(END_CYCLE_DURATION).with_event(END_CYCLE_DURATION, END_CUE),
+    ));
+    let fade = Track::new(LinearRgba::BLACK).to(END_CYCLE_DURATION, LinearRgba::NONE, Ease::Linear);
+    root.insert(TweenBackground {
+        timeline: root.id(),
+        track: fade,
+    });
+    root.with_children(|cmds| {
         cmds.spawn(
             default_text("YOU SURVIVED THE STORM", 64.0, &asset_server).with_style(Style {
                 padding: UiRect::all(Val::Px(PADDING)),
@@ -44,20 +52,19 @@ fn setup_intro(mut cmds: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
-pub fn update_root_visibility(
-    mut q_root: Query<&mut BackgroundColor, With<EndCycleRoot>>,
-    time: Res<EndCycleTime>,
-) {
-    q_root.single_mut().0 = Color::BLACK.with_alpha(1.0 - time.0 / END_CYCLE_DURATION);
+// The storm has moved the sand, while the screen is black
+fn migrate_dunes(mut tp: ResMut<TerrainParams>) {
+    tp.migrate_dunes();
 }
 
-pub fn update_end_cycle(
-    time: Res<Time>,
-    mut intro_time: ResMut<EndCycleTime>,
+fn end_cycle(
+    mut ev_cue: EventReader<TimelineCue>,
+    q_root: Query<(), With<EndCycleRoot>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    intro_time.0 += time.delta_seconds();
-    if intro_time.0 > END_CYCLE_DURATION {
-        next_state.set(GameState::InCycle);
+    for cue in ev_cue.read() {
+        if cue.name == END_CUE && q_root.contains(cue.timeline) {
+            next_state.set(GameState::InCycle);
+        }
     }
 }
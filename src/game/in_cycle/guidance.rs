@@ -0,0 +1,207 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::{GameState, GameTime, CYCLE_LENGTH},
+    materials::beam::{beam_bundle, BeamMaterial, BeamMesh},
+    movement::Speed,
+    player::Player,
+    settings::StormAssist,
+    shelter::{PlayerIsSafe, ShelterSafeZone},
+};
+
+/// With the storm assist on, points to the nearest shelter from when the bell rings at half
+/// cycle
+pub struct GuidancePlugin;
+impl Plugin for GuidancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShelterGuidance>()
+            .add_systems(OnEnter(GameState::InCycle), clear_guidance)
+            .add_systems(
+                Update,
+                (pick_shelter, stop_guidance, guidance_beam, guidance_text)
+                    .chain()
+                    .run_if(in_state(GameState::InCycle)),
+            );
+    }
+}
+
+// Paths wind around dunes, so the straight line is only part of the way
+const DETOUR_FACTOR: f32 = 1.3;
+// The nearest shelter is picked again every time the player has walked this far, in meters
+const REPICK_DISTANCE: f32 = 25.0;
+const BEAM_COLOR: LinearRgba = LinearRgba::new(1.0, 6.0, 2.0, 0.6);
+const BEAM_RADIUS: f32 = 1.5;
+const BEAM_HEIGHT: f32 = 2000.0;
+const REACHABLE_TEXT_COLOR: Color = Color::srgb(0.35, 0.8, 0.4);
+const LATE_TEXT_COLOR: Color = Color::srgb(0.91, 0.32, 0.25);
+
+/// Safe zone of the shelter the player is pointed to
+#[derive(Resource, Default, PartialEq)]
+pub struct ShelterGuidance(pub Option<Vec3>);
+
+#[derive(Component)]
+struct GuidanceBeam;
+
+#[derive(Component)]
+struct GuidanceUi;
+
+#[derive(Component)]
+struct GuidanceText;
+
+/// Seconds it takes to walk to `target`
+fn travel_time(from: Vec3, target: Vec3, speed: f32) -> f32 {
+    from.xz().distance(target.xz()) * DETOUR_FACTOR / speed
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds.max(0.0).round() as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn clear_guidance(mut guidance: ResMut<ShelterGuidance>) {
+    guidance.0 = None;
+}
+
+/// The shelter quickest to walk to, also when the assist is turned on during the storm or the
+/// player leaves a shelter, and again as the player moves. The text tells whether it can be
+/// reached in the time left.
+fn pick_shelter(
+    time: Res<GameTime>,
+    storm_assist: Res<StormAssist>,
+    player_is_safe: Res<PlayerIsSafe>,
+    q_player: Query<(&GlobalTransform, &Speed), With<Player>>,
+    q_safe_zones: Query<&GlobalTransform, With<ShelterSafeZone>>,
+    mut guidance: ResMut<ShelterGuidance>,
+    // Where the player was when the shelter was picked
+    mut picked_at: Local<Vec3>,
+) {
+    if !storm_assist.0 || player_is_safe.0 || time.time < CYCLE_LENGTH * 0.5 {
+        return;
+    }
+    let Ok((player_tr, speed)) = q_player.get_single() else {
+        return;
+    };
+    let player_pos = player_tr.translation();
+    let walked = picked_at.xz().distance(player_pos.xz());
+    if guidance.0.is_some() && walked < REPICK_DISTANCE {
+        return;
+    }
+    *picked_at = player_pos;
+    let picked = q_safe_zones
+        .iter()
+        .map(|tr| {
+            let pos = tr.translation();
+            (pos, travel_time(player_pos, pos, speed.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(pos, _)| pos);
+    guidance.set_if_neq(ShelterGuidance(picked));
+}
+
+/// Once in a shelter, or when the assist is turned off
+fn stop_guidance(
+    player_is_safe: Res<PlayerIsSafe>,
+    storm_assist: Res<StormAssist>,
+    mut guidance: ResMut<ShelterGuidance>,
+) {
+    if guidance.0.is_some() && (player_is_safe.0 || !storm_assist.0) {
+        guidance.0 = None;
+    }
+}
+
+fn guidance_beam(
+    mut cmds: Commands,
+    guidance: Res<ShelterGuidance>,
+    beam_mesh: Res<BeamMesh>,
+    mut beam_materials: ResMut<Assets<BeamMaterial>>,
+    mut q_beam: Query<(Entity, &mut Transform), With<GuidanceBeam>>,
+) {
+    match (guidance.0, q_beam.get_single_mut()) {
+        (Some(target), Ok((_, mut tr))) => {
+            tr.translation = target;
+        }
+        (Some(target), Err(_)) => {
+            cmds.spawn((
+                Name::new("Shelter guidance beam"),
+                GuidanceBeam,
+                StateScoped(GameState::InCycle),
+                beam_bundle(
+                    &beam_mesh,
+                    beam_materials.add(BeamMaterial { color: BEAM_COLOR }),
+                    target,
+                    BEAM_RADIUS,
+                    BEAM_HEIGHT,
+                ),
+            ));
+        }
+        (None, Ok((e, _))) => {
+            cmds.entity(e).despawn_recursive();
+        }
+        (None, Err(_)) => {}
+    }
+}
+
+fn guidance_text(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    guidance: Res<ShelterGuidance>,
+    time: Res<GameTime>,
+    q_player: Query<(&GlobalTransform, &Speed), With<Player>>,
+    q_ui: Query<Entity, With<GuidanceUi>>,
+    mut q_text: Query<&mut Text, With<GuidanceText>>,
+) {
+    let estimate = guidance.0.zip(q_player.get_single().ok());
+    let Some((target, (player_tr, speed))) = estimate else {
+        for e in &q_ui {
+            cmds.entity(e).despawn_recursive();
+        }
+        return;
+    };
+
+    let travel = travel_time(player_tr.translation(), target, speed.0);
+    let remaining = CYCLE_LENGTH - time.time;
+    let dist = player_tr.translation().xz().distance(target.xz());
+    let (only, color) = match travel < remaining {
+        true => ("", REACHABLE_TEXT_COLOR),
+        false => ("only ", LATE_TEXT_COLOR),
+    };
+    let value = format!(
+        "Nearest shelter {dist:.0} m away, about {} on foot, {only}{} left",
+        format_time(travel),
+        format_time(remaining)
+    );
+
+    if let Ok(mut text) = q_text.get_single_mut() {
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+        return;
+    }
+    cmds.spawn((
+        Name::new("Shelter guidance"),
+        GuidanceUi,
+        StateScoped(GameState::InCycle),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                width: Val::Percent(100.0),
+                top: Val::Px(88.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            GuidanceText,
+            TextBundle::from_section(
+                value,
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color,
+                },
+            ),
+        ));
+    });
+}
@@ -1,15 +1,21 @@
+mod guidance;
+
 use bevy::prelude::*;
 
 use crate::{
-    battery::BatterySlot, sandstorm::SandstormIntensity, shelter::PlayerIsSafe, tower::RingBell,
+    battery::{BatteryCount, BatterySlot},
+    sandstorm::SandstormIntensity,
+    shelter::PlayerIsSafe,
+    tower::RingBell,
 };
 
-use super::{GameState, GameTime, CYCLE_LENGTH};
+use super::{inhibitor::InhibitorProgress, monolith::Sigils, GameState, GameTime, CYCLE_LENGTH};
 
 pub struct InCyclePlugin;
 impl Plugin for InCyclePlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::InCycle), enter_cycle)
+        app.add_plugins(guidance::GuidancePlugin)
+            .add_systems(OnEnter(GameState::InCycle), enter_cycle)
             .add_systems(
                 Update,
                 (
@@ -24,9 +30,21 @@ impl Plugin for InCyclePlugin {
     }
 }
 
-fn enter_cycle(mut cmds: Commands) {
+/// When the storm of this cycle starts, delayed by the sigils collected before it, and the
+/// intensity it reaches, lowered by the inhibitor stages powered before it
+#[derive(Resource)]
+struct StormSchedule {
+    start: f32,
+    peak: f32,
+}
+
+fn enter_cycle(mut cmds: Commands, progress: Res<InhibitorProgress>, sigils: Res<Sigils>) {
     cmds.add(super::checkpoint::save_checkpoint);
     cmds.insert_resource(GameTime::default());
+    cmds.insert_resource(StormSchedule {
+        start: CYCLE_LENGTH * 0.5 + sigils.calm_extension(),
+        peak: progress.storm_peak(),
+    });
 }
 
 fn update_game_time(time: Res<Time>, mut game_time: ResMut<GameTime>) {
@@ -34,8 +52,13 @@ fn update_game_time(time: Res<Time>, mut game_time: ResMut<GameTime>) {
     game_time.time += time.delta_seconds();
 }
 
-fn control_storm(mut storm_intensity: ResMut<SandstormIntensity>, time: Res<GameTime>) {
-    storm_intensity.0 = (time.time - CYCLE_LENGTH * 0.5).max(0.0) / (CYCLE_LENGTH / 2.0);
+fn control_storm(
+    mut storm_intensity: ResMut<SandstormIntensity>,
+    time: Res<GameTime>,
+    schedule: Res<StormSchedule>,
+) {
+    storm_intensity.0 =
+        (time.time - schedule.start).max(0.0) / (CYCLE_LENGTH - schedule.start) * schedule.peak;
 }
 
 fn end_cycle(
@@ -64,8 +87,15 @@ fn ring_bell(time: Res<GameTime>, mut ev_ring: EventWriter<RingBell>) {
     }
 }
 
-fn trigger_win(q_slots: Query<&BatterySlot>, mut next_state: ResMut<NextState<GameState>>) {
-    if q_slots.iter().all(|slot| slot.filled) {
-        next_state.set(GameState::Won);
+fn trigger_win(
+    q_slots: Query<&BatterySlot>,
+    battery_count: Res<BatteryCount>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    // Not before the tower and its slots are loaded
+    let slots = q_slots.iter().count();
+    let filled = q_slots.iter().filter(|slot| slot.filled).count();
+    if slots > 0 && filled >= battery_count.needed(slots) {
+        next_state.set(GameState::Activation);
     }
 }
@@ -0,0 +1,331 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::*;
+
+use crate::{
+    battery::{BatteryCount, BatterySlot},
+    camera::CameraMode,
+    menu::styling::{bottom_root, default_text},
+    player::Player,
+    sandstorm::SandstormIntensity,
+    tower::{RingBell, TowerBell},
+    util::spatial_playback_remove,
+};
+
+use super::GameState;
+
+/// Every filled slot powers a stage of the weather inhibitor, the last one starts it
+pub struct InhibitorPlugin;
+impl Plugin for InhibitorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<InhibitorProgress>()
+            .init_resource::<InhibitorProgress>()
+            .add_systems(Update, (count_stages, light_stages))
+            .add_systems(OnEnter(GameState::InCycle), spawn_progress_hud)
+            .add_systems(
+                Update,
+                update_progress_hud.run_if(in_state(GameState::InCycle)),
+            )
+            .add_systems(OnEnter(GameState::Activation), start_activation)
+            .add_systems(
+                Update,
+                update_activation.run_if(in_state(GameState::Activation)),
+            )
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Activation,
+                    entered: GameState::Won,
+                },
+                end_activation,
+            );
+    }
+}
+
+// Each powered stage lowers the peak of the storms of the following cycles
+const STORM_REDUCTION_PER_STAGE: f32 = 0.15;
+
+const STAGE_LIGHT_COLOR: Color = Color::srgb(0.3, 0.8, 1.0);
+const STAGE_LIGHT_HEIGHT: f32 = 1.5;
+const STAGE_SOUND_VOLUME: f32 = 3.0;
+
+const CELL_SIZE: f32 = 18.0;
+const POWERED_CELL_COLOR: Color = Color::srgb(0.3, 0.8, 1.0);
+const UNPOWERED_CELL_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.5);
+
+const ACTIVATION_TIME: f32 = 8.0;
+// The camera circles the tower during the activation
+const ORBIT_RADIUS: f32 = 70.0;
+const ORBIT_HEIGHT: f32 = 25.0;
+const ORBIT_ANGLE: f32 = FRAC_PI_2;
+
+/// Stages of the weather inhibitor powered so far, counted from the filled slots
+#[derive(Resource, Reflect, Default, PartialEq)]
+#[reflect(Resource)]
+pub struct InhibitorProgress(pub usize);
+
+impl InhibitorProgress {
+    /// One stage per filled slot
+    pub fn count<'a>(slots: impl Iterator<Item = &'a BatterySlot>) -> Self {
+        Self(slots.filter(|slot| slot.filled).count())
+    }
+
+    /// Intensity the storm reaches at the end of a cycle
+    pub fn storm_peak(&self) -> f32 {
+        (1.0 - STORM_REDUCTION_PER_STAGE * self.0 as f32).max(0.0)
+    }
+}
+
+/// Shines above a powered slot
+#[derive(Component)]
+struct StageLight(Entity);
+
+fn count_stages(q_slots: Query<&BatterySlot>, mut progress: ResMut<InhibitorProgress>) {
+    progress.set_if_neq(InhibitorProgress::count(q_slots.iter()));
+}
+
+/// Slots are emptied again when a checkpoint from before they were filled is loaded
+fn light_stages(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    q_slots: Query<(Entity, &BatterySlot, Option<&StageLight>), Changed<BatterySlot>>,
+) {
+    for (slot_e, slot, light) in &q_slots {
+        match (slot.filled, light) {
+            (true, None) => {
+                let light_e = cmds
+                    .spawn((
+                        Name::new("Stage light"),
+                        PointLightBundle {
+                            point_light: PointLight {
+                                color: STAGE_LIGHT_COLOR,
+                                intensity: 200_000.0,
+                                range: 20.0,
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(0.0, STAGE_LIGHT_HEIGHT, 0.0),
+                            ..default()
+                        },
+                        AudioBundle {
+                            source: asset_server.load("audio/sfx/beacon_light.ogg"),
+                            settings: spatial_playback_remove(STAGE_SOUND_VOLUME, 0.4),
+                        },
+                    ))
+                    .set_parent(slot_e)
+                    .id();
+                cmds.entity(slot_e).insert(StageLight(light_e));
+            }
+            (false, Some(light)) => {
+                cmds.entity(light.0).despawn_recursive();
+                cmds.entity(slot_e).remove::<StageLight>();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Component)]
+struct ProgressCell(usize);
+
+fn spawn_progress_hud(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    battery_count: Res<BatteryCount>,
+    q_slots: Query<(), With<BatterySlot>>,
+) {
+    let cells = battery_count.needed(q_slots.iter().count());
+    cmds.spawn((
+        Name::new("Inhibitor progress"),
+        StateScoped(GameState::InCycle),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(16.0),
+                right: Val::Px(16.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn(default_text("Inhibitor", 20.0, &asset_server));
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                for i in 0..cells {
+                    parent.spawn((
+                        ProgressCell(i),
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(CELL_SIZE),
+                                height: Val::Px(CELL_SIZE),
+                                ..default()
+                            },
+                            background_color: UNPOWERED_CELL_COLOR.into(),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    });
+}
+
+fn update_progress_hud(
+    progress: Res<InhibitorProgress>,
+    mut q_cells: Query<(&mut BackgroundColor, &ProgressCell)>,
+) {
+    for (mut color, cell) in &mut q_cells {
+        *color = match cell.0 < progress.0 {
+            true => POWERED_CELL_COLOR,
+            false => UNPOWERED_CELL_COLOR,
+        }
+        .into();
+    }
+}
+
+/// What the camera follows during the activation
+#[derive(Component)]
+struct ActivationViewpoint {
+    center: Vec3,
+}
+
+#[derive(Resource)]
+struct ActivationTime(f32);
+
+/// Circles the tower from a quarter turn away, `t` going from 0 to 1
+fn orbit(center: Vec3, t: f32) -> Transform {
+    let offset =
+        Quat::from_rotation_y(t * ORBIT_ANGLE) * Vec3::new(0.0, ORBIT_HEIGHT, ORBIT_RADIUS);
+    Transform::from_translation(center + offset).looking_at(center, Vec3::Y)
+}
+
+fn start_activation(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    q_bell: Query<&GlobalTransform, With<TowerBell>>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut ev_ring: EventWriter<RingBell>,
+) {
+    cmds.insert_resource(ActivationTime(0.0));
+    ev_ring.send(RingBell(3));
+    if let Ok(bell_tr) = q_bell.get_single() {
+        let center = bell_tr.translation();
+        let viewpoint = cmds
+            .spawn((
+                Name::new("Activation viewpoint"),
+                ActivationViewpoint { center },
+                StateScoped(GameState::Activation),
+                TransformBundle::from_transform(orbit(center, 0.0)),
+            ))
+            .id();
+        *camera_mode = CameraMode::Follow(viewpoint);
+    }
+    cmds.spawn((bottom_root(), StateScoped(GameState::Activation)))
+        .with_children(|cmds| {
+            cmds.spawn(default_text(
+                "The weather inhibitor hums to life...",
+                64.0,
+                &asset_server,
+            ));
+        });
+}
+
+fn update_activation(
+    time: Res<Time>,
+    mut activation_time: ResMut<ActivationTime>,
+    mut q_viewpoint: Query<(&mut Transform, &ActivationViewpoint)>,
+    mut storm_intensity: ResMut<SandstormIntensity>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    activation_time.0 += time.delta_seconds();
+    let t = (activation_time.0 / ACTIVATION_TIME).min(1.0);
+    for (mut tr, viewpoint) in &mut q_viewpoint {
+        *tr = orbit(viewpoint.center, t);
+    }
+    // The storm calms down as the inhibitor starts
+    storm_intensity.0 = storm_intensity.0.min(1.0 - t);
+    if activation_time.0 >= ACTIVATION_TIME {
+        next_state.set(GameState::Won);
+    }
+}
+
+fn end_activation(mut camera_mode: ResMut<CameraMode>, q_player: Query<Entity, With<Player>>) {
+    if let Ok(player_e) = q_player.get_single() {
+        *camera_mode = CameraMode::Control(player_e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+    use crate::{
+        battery::{Battery, BatterySlot, HeldBattery, SlotBattery, TakenFrom},
+        game::{
+            checkpoint::{load_checkpoint, save_checkpoint},
+            monolith::Sigils,
+        },
+        map::{MapFog, MapJournal},
+        player::{Inventory, Player},
+        sandstorm::SandDrifts,
+        terrain::{TerrainEdits, TerrainParams},
+    };
+
+    use super::{count_stages, InhibitorProgress};
+
+    #[test]
+    fn stages_powered_after_checkpoint_are_lost() {
+        let mut world = World::new();
+        world.init_resource::<InhibitorProgress>();
+        world.init_resource::<MapJournal>();
+        world.init_resource::<MapFog>();
+        world.init_resource::<Sigils>();
+        world.init_resource::<TerrainEdits>();
+        world.init_resource::<SandDrifts>();
+        world.insert_resource(TerrainParams::with_seed(1337));
+        world.spawn((
+            Player,
+            Transform::default(),
+            Inventory { batteries: vec![] },
+        ));
+        let powered = world.spawn(BatterySlot { filled: true }).id();
+        let slot = world.spawn(BatterySlot { filled: false }).id();
+        let battery = world.spawn((Battery, Transform::default())).id();
+        world.run_system_once(count_stages);
+        save_checkpoint(&mut world);
+        assert_eq!(world.resource::<InhibitorProgress>().0, 1);
+
+        // Taken and placed after the checkpoint
+        let taken_from = Transform::from_xyz(10.0, 2.0, -5.0);
+        world.entity_mut(battery).insert(TakenFrom {
+            parent: None,
+            transform: taken_from,
+            on_terrain: true,
+        });
+        world.get_mut::<BatterySlot>(slot).unwrap().filled = true;
+        world.entity_mut(slot).insert(SlotBattery(battery));
+        world.run_system_once(count_stages);
+        assert_eq!(world.resource::<InhibitorProgress>().0, 2);
+
+        load_checkpoint(&mut world);
+        world.run_system_once(count_stages);
+        assert_eq!(world.resource::<InhibitorProgress>().0, 1);
+        assert!(world.get::<BatterySlot>(powered).unwrap().filled);
+        assert!(!world.get::<BatterySlot>(slot).unwrap().filled);
+        assert!(world.get::<SlotBattery>(slot).is_none());
+        // Put back where it was taken by `hold_batteries`
+        assert!(world.get::<HeldBattery>(battery).is_some());
+        assert_eq!(
+            world.get::<TakenFrom>(battery).unwrap().transform,
+            taken_from
+        );
+    }
+}
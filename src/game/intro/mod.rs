@@ -4,7 +4,7 @@ use crate::{
     camera::CameraMode,
     menu::styling::{bottom_root, default_text},
     player::Player,
-    terrain::TerrainParams,
+    terrain::TerrainSampler,
 };
 
 use super::{GameState, SpawnPoint};
@@ -16,7 +16,14 @@ impl Plugin for IntroPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<IntroViewpoint>()
             .add_systems(OnEnter(GameState::Intro), setup_intro)
-            .add_systems(OnExit(GameState::Intro), exit_intro)
+            // Not when a new world interrupts the intro
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Intro,
+                    entered: GameState::InCycle,
+                },
+                exit_intro,
+            )
             .add_systems(Update, update_intro.run_if(in_state(GameState::Intro)));
     }
 }
@@ -66,12 +73,12 @@ pub fn exit_intro(
     mut camera_mode: ResMut<CameraMode>,
     mut q_player: Query<(Entity, &mut Transform), With<Player>>,
     q_spawn_point: Query<&Transform, (With<SpawnPoint>, Without<Player>)>,
-    terrain_params: Res<TerrainParams>,
+    terrain: Res<TerrainSampler>,
 ) {
     let (player_e, mut player_tr) = q_player.single_mut();
     *camera_mode = CameraMode::Control(player_e);
 
     let xz = q_spawn_point.single().translation.xz();
-    let height = terrain_params.get_height(xz) + 4.0;
+    let height = terrain.height(xz) + 4.0;
     player_tr.translation = xz.extend(height).xzy();
 }
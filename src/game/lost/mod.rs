@@ -1,7 +1,10 @@
 use bevy::prelude::*;
 
 use crate::{
-    menu::styling::{button_bundle, central_panel, default_text, opaque_root, PADDING},
+    menu::{
+        navigation::{ButtonActivated, FocusedButton, MenuNavigationSet},
+        styling::{button_bundle, central_panel, default_text, opaque_root, PADDING},
+    },
     sandstorm::SandstormIntensity,
 };
 
@@ -11,7 +14,7 @@ pub struct LostPlugin;
 impl Plugin for LostPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Lost), setup_lose)
-            .add_systems(Update, interact_restart);
+            .add_systems(Update, interact_restart.after(MenuNavigationSet));
     }
 }
 
@@ -21,7 +24,11 @@ pub struct LoseMenu;
 #[derive(Component)]
 pub struct Restart;
 
-fn setup_lose(mut cmds: Commands, asset_server: Res<AssetServer>) {
+fn setup_lose(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    mut focused: ResMut<FocusedButton>,
+) {
     cmds.insert_resource(SandstormIntensity(0.0));
     cmds.spawn((opaque_root(), LoseMenu, StateScoped(GameState::Lost)))
         .with_children(|cmds| {
@@ -34,24 +41,29 @@ fn setup_lose(mut cmds: Commands, asset_server: Res<AssetServer>) {
                         },
                     ),
                 );
-                cmds.spawn((button_bundle(), Restart))
+                let restart = cmds
+                    .spawn((button_bundle(), Restart))
                     .with_children(|cmds| {
                         cmds.spawn(default_text(
                             "RESTART AT LAST CHECKPOINT",
                             32.0,
                             &asset_server,
                         ));
-                    });
+                    })
+                    .id();
+                // Focus it right away so it can be confirmed without a mouse
+                focused.0 = Some(restart);
             });
         });
 }
 
 pub fn interact_restart(
     mut cmds: Commands,
-    q_button: Query<&Interaction, (Changed<Interaction>, With<Restart>)>,
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<Restart>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if let Ok(Interaction::Pressed) = q_button.get_single() {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
         cmds.add(super::checkpoint::load_checkpoint);
         next_state.set(GameState::InCycle);
     }
@@ -1,6 +1,7 @@
 mod checkpoint;
 mod end_cycle;
 mod in_cycle;
+mod inhibitor;
 mod intro;
 mod lost;
 mod monolith;
@@ -10,12 +11,18 @@ use bevy::prelude::*;
 use checkpoint::CheckpointPlugin;
 use end_cycle::EndCyclePlugin;
 use in_cycle::InCyclePlugin;
+use inhibitor::InhibitorPlugin;
 use intro::{IntroPlugin, IntroViewpoint};
 use lost::LostPlugin;
 use monolith::MonolithPlugin;
 use won::WonPlugin;
 
-use crate::util::switch_to_state;
+use crate::{
+    camera::CameraMode,
+    player::{Inventory, SpawnPlayer},
+    terrain::PlacementSet,
+    util::switch_to_state,
+};
 
 pub const CYCLE_LENGTH: f32 = 60.0 * 5.0;
 
@@ -27,6 +34,7 @@ impl Plugin for GamePlugin {
             .add_plugins((
                 IntroPlugin,
                 InCyclePlugin,
+                InhibitorPlugin,
                 EndCyclePlugin,
                 LostPlugin,
                 WonPlugin,
@@ -41,7 +49,8 @@ impl Plugin for GamePlugin {
                 switch_to_state(GameState::Intro).run_if(in_state(GameState::None).and_then(
                     |q_added_intro: Query<(), Added<IntroViewpoint>>| !q_added_intro.is_empty(),
                 )),
-            );
+            )
+            .add_systems(Update, new_game.in_set(PlacementSet::Clear));
     }
 }
 
@@ -67,6 +76,8 @@ enum GameState {
     Intro,
     InCycle,
     EndCycle,
+    /// The last battery has been placed, the weather inhibitor starts
+    Activation,
     Lost,
     Won,
 }
@@ -74,3 +85,23 @@ enum GameState {
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct SpawnPoint;
+
+/// A new world starts the game over, the intro plays again once its tower is loaded
+fn new_game(
+    mut cmds: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut camera_mode: ResMut<CameraMode>,
+    mut ev_spawn_player: EventWriter<SpawnPlayer>,
+    mut q_inventory: Query<&mut Inventory>,
+) {
+    // The batteries are gone with the old world
+    for mut inventory in &mut q_inventory {
+        inventory.batteries.clear();
+    }
+    cmds.insert_resource(GameTime::default());
+    cmds.remove_resource::<checkpoint::Checkpoint>();
+    cmds.insert_resource(monolith::Sigils::default());
+    *camera_mode = CameraMode::Free;
+    ev_spawn_player.send(SpawnPlayer(Vec3::Y * 2.0));
+    next_state.set(GameState::None);
+}
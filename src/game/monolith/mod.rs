@@ -1,12 +1,44 @@
+mod placement;
+
+use avian3d::prelude::{Collider, Sensor};
 use bevy::prelude::*;
 
-use crate::player::Player;
+use crate::{
+    battery::Battery,
+    interaction::{Interactable, Interacted, InteractionSet},
+    map::{Landmark, LandmarkKind, MapJournal},
+    menu::styling::default_text,
+    player::Inventory,
+};
+
+use super::{GameState, CYCLE_LENGTH};
 
+/// Touching the sigil of a monolith reveals a battery and gives more time before the storms
 pub struct MonolithPlugin;
 impl Plugin for MonolithPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Monolith>()
-            .add_systems(Update, (monolith_collect, monolith_light_up));
+        app.add_plugins(placement::MonolithPlacementPlugin)
+            .register_type::<Monolith>()
+            .register_type::<CollectedMonolith>()
+            .register_type::<Sigils>()
+            .init_resource::<Sigils>()
+            .add_systems(Startup, setup_sigil_materials)
+            .add_systems(OnEnter(GameState::InCycle), spawn_sigil_hud)
+            .add_systems(
+                Update,
+                (
+                    monolith_interactables,
+                    (
+                        monolith_collect,
+                        sync_monoliths.run_if(resource_changed::<Sigils>),
+                    )
+                        .chain()
+                        .after(InteractionSet),
+                    light_sigil_material,
+                    forget_taken_batteries,
+                    update_sigil_hud.run_if(in_state(GameState::InCycle)),
+                ),
+            );
     }
 }
 
@@ -14,40 +46,223 @@ impl Plugin for MonolithPlugin {
 #[reflect(Component)]
 pub struct Monolith;
 
+/// Its sigil glows, kept in sync with [`Sigils`]
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct CollectedMonolith;
 
+/// Monoliths whose sigil has been touched, kept in checkpoints
+#[derive(Resource, Reflect, Clone, Default)]
+#[reflect(Resource)]
+pub struct Sigils {
+    pub collected: Vec<Entity>,
+}
+
+impl Sigils {
+    /// Seconds the storm of a cycle starts later by
+    pub fn calm_extension(&self) -> f32 {
+        (CALM_EXTENSION_PER_SIGIL * self.collected.len() as f32).min(MAX_CALM_EXTENSION)
+    }
+}
+
+const CALM_EXTENSION_PER_SIGIL: f32 = 15.0;
+// The storm still has a quarter of the cycle to build up
+const MAX_CALM_EXTENSION: f32 = CYCLE_LENGTH * 0.25;
+const SIGIL_GLOW: f32 = 100.0;
+
+// Size of the collider around the monolith model, which goes from 1 m below its origin
+const MONOLITH_RADIUS: f32 = 0.9;
+const MONOLITH_HEIGHT: f32 = 5.6;
+const MONOLITH_RANGE: f32 = 4.0;
+const TOUCH_PROMPT: &str = "Press <interact> to touch the sigil.";
+
+fn monolith_interactables(mut cmds: Commands, q_monolith: Query<Entity, Added<Monolith>>) {
+    for e in &q_monolith {
+        cmds.entity(e)
+            .insert(Interactable::new(TOUCH_PROMPT).with_range(MONOLITH_RANGE))
+            .with_children(|parent| {
+                parent.spawn((
+                    Collider::cylinder(MONOLITH_RADIUS, MONOLITH_HEIGHT),
+                    Sensor,
+                    TransformBundle::from_transform(Transform::from_xyz(
+                        0.0,
+                        MONOLITH_HEIGHT / 2.0 - 1.0,
+                        0.0,
+                    )),
+                ));
+            });
+    }
+}
+
+/// The sigil reveals the battery lying closest to the monolith that isn't on the map yet
 fn monolith_collect(
+    mut ev_interacted: EventReader<Interacted>,
+    q_monolith: Query<&GlobalTransform, (With<Monolith>, Without<CollectedMonolith>)>,
+    // Batteries lying around have a collider, held and placed ones don't
+    q_batteries: Query<(Entity, &GlobalTransform), (With<Battery>, With<Collider>)>,
+    mut sigils: ResMut<Sigils>,
+    mut journal: ResMut<MapJournal>,
+) {
+    for Interacted(e) in ev_interacted.read() {
+        let Ok(monolith_tr) = q_monolith.get(*e) else {
+            continue;
+        };
+        if sigils.collected.contains(e) {
+            continue;
+        }
+        sigils.collected.push(*e);
+
+        let monolith_pos = monolith_tr.translation();
+        let hint = q_batteries
+            .iter()
+            .filter(|(battery_e, _)| {
+                !journal
+                    .landmarks
+                    .iter()
+                    .any(|landmark| landmark.entity == *battery_e)
+            })
+            .min_by(|(_, a), (_, b)| {
+                let dist = |tr: &GlobalTransform| tr.translation().distance_squared(monolith_pos);
+                dist(a).total_cmp(&dist(b))
+            });
+        if let Some((battery_e, battery_tr)) = hint {
+            journal.landmarks.push(Landmark {
+                entity: battery_e,
+                kind: LandmarkKind::Battery,
+                pos: battery_tr.translation().xz(),
+            });
+        }
+    }
+}
+
+/// Material of the sigils in the scene, and a glowing copy of it shared by collected monoliths
+#[derive(Resource)]
+struct SigilMaterials {
+    dark: Handle<StandardMaterial>,
+    lit: Handle<StandardMaterial>,
+}
+
+fn setup_sigil_materials(
     mut cmds: Commands,
-    q_player: Query<&GlobalTransform, With<Player>>,
-    q_monolith: Query<(Entity, &GlobalTransform), (With<Monolith>, Without<CollectedMonolith>)>,
+    asset_server: Res<AssetServer>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    cmds.insert_resource(SigilMaterials {
+        dark: asset_server.load("levels/Scene.glb#Material0"),
+        lit: materials.reserve_handle(),
+    });
+}
+
+/// The glowing copy follows the material in the scene
+fn light_sigil_material(
+    mut ev_asset: EventReader<AssetEvent<StandardMaterial>>,
+    sigil_materials: Res<SigilMaterials>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    let Ok(player_tr) = q_player.get_single() else {
+    let dark = &sigil_materials.dark;
+    if !ev_asset
+        .read()
+        .any(|ev| ev.is_added(dark) || ev.is_modified(dark))
+    {
         return;
-    };
-    for (monolith_e, tr) in &q_monolith {
-        if tr.translation().distance(player_tr.translation()) < 3.0 {
-            cmds.entity(monolith_e).insert(CollectedMonolith);
-        }
     }
+    let Some(mut lit) = materials.get(dark).cloned() else {
+        return;
+    };
+    lit.emissive = LinearRgba::WHITE * SIGIL_GLOW;
+    materials.insert(&sigil_materials.lit, lit);
 }
 
-fn monolith_light_up(
-    q_monolith: Query<Entity, Added<CollectedMonolith>>,
+/// Monoliths touched since the checkpoint that was loaded go dark again
+fn sync_monoliths(
+    mut cmds: Commands,
+    sigils: Res<Sigils>,
+    sigil_materials: Res<SigilMaterials>,
+    q_monolith: Query<(Entity, Has<CollectedMonolith>), With<Monolith>>,
     q_children: Query<&Children>,
     q_name: Query<&Name>,
-    q_material: Query<&Handle<StandardMaterial>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut q_material: Query<&mut Handle<StandardMaterial>>,
 ) {
-    for e in &q_monolith {
+    for (e, was_collected) in &q_monolith {
+        let collected = sigils.collected.contains(&e);
+        if collected == was_collected {
+            continue;
+        }
+        match collected {
+            true => {
+                cmds.entity(e)
+                    .insert(CollectedMonolith)
+                    .remove::<Interactable>();
+            }
+            false => {
+                cmds.entity(e)
+                    .remove::<CollectedMonolith>()
+                    .insert(Interactable::new(TOUCH_PROMPT).with_range(MONOLITH_RANGE));
+            }
+        }
         for e in q_children.iter_descendants(e) {
-            if q_name.get(e).map(|n| n.as_str()) == Ok("Sigil.Mesh") {
-                let Ok(mat_handle) = q_material.get(e) else {
-                    continue;
+            if q_name.get(e).map(|n| n.as_str()) != Ok("Sigil.Mesh") {
+                continue;
+            }
+            if let Ok(mut mat_handle) = q_material.get_mut(e) {
+                *mat_handle = match collected {
+                    true => sigil_materials.lit.clone(),
+                    false => sigil_materials.dark.clone(),
                 };
-                materials.get_mut(mat_handle).unwrap().emissive = LinearRgba::WHITE * 100.0;
             }
         }
     }
 }
+
+/// Batteries revealed by sigils leave the map once taken
+fn forget_taken_batteries(
+    q_inventory: Query<&Inventory, Changed<Inventory>>,
+    mut journal: ResMut<MapJournal>,
+) {
+    let Ok(inventory) = q_inventory.get_single() else {
+        return;
+    };
+    let taken = |landmark: &Landmark| {
+        landmark.kind == LandmarkKind::Battery && inventory.batteries.contains(&landmark.entity)
+    };
+    if journal.landmarks.iter().any(taken) {
+        journal.landmarks.retain(|landmark| !taken(landmark));
+    }
+}
+
+#[derive(Component)]
+struct SigilText;
+
+fn spawn_sigil_hud(mut cmds: Commands, asset_server: Res<AssetServer>) {
+    cmds.spawn((
+        Name::new("Sigil count"),
+        StateScoped(GameState::InCycle),
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(72.0),
+                right: Val::Px(16.0),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((SigilText, default_text("", 20.0, &asset_server)));
+    });
+}
+
+/// Out of the monoliths in the world, placement may find room for fewer than asked
+fn update_sigil_hud(
+    sigils: Res<Sigils>,
+    q_monoliths: Query<(), With<Monolith>>,
+    mut q_text: Query<&mut Text, With<SigilText>>,
+) {
+    let count = q_monoliths.iter().count();
+    for mut text in &mut q_text {
+        let value = format!("Sigils {}/{count}", sigils.collected.len());
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
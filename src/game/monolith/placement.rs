@@ -0,0 +1,160 @@
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    map::{LandmarkKind, MapLandmark},
+    pyramids::{monolith_spot, Pyramid},
+    shelter::Shelter,
+    terrain::{PlacementSet, TerrainParams, TerrainSampler},
+    util::{despawn_all, poisson_disc_sampling_with},
+};
+
+use super::Monolith;
+
+/// Raises monoliths in pyramids and across the world from the world seed
+pub struct MonolithPlacementPlugin;
+impl Plugin for MonolithPlacementPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MonolithCount>()
+            .init_resource::<MonolithCount>()
+            .add_systems(Update, despawn_all::<Monolith>.in_set(PlacementSet::Clear))
+            .add_systems(Update, place_monoliths.in_set(PlacementSet::Objects));
+    }
+}
+
+/// Monoliths raised in the world
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct MonolithCount(pub usize);
+
+impl Default for MonolithCount {
+    fn default() -> Self {
+        Self(5)
+    }
+}
+
+const MONOLITH_SPACING: f32 = 800.0;
+const MONOLITH_REGION: f32 = 4000.0;
+// Monoliths are kept away from pyramids and shelters
+const SCENE_CLEARANCE: f32 = 150.0;
+// The model goes from 1 m below its origin, it is sunk a bit more to stand on slopes
+const MONOLITH_ELEVATION: f32 = 0.7;
+// Parts of the monolith model in the scene it was authored in
+const MONOLITH_SCALE: Vec3 = Vec3::new(1.3675, 1.0, 1.3675);
+const SIGIL_OFFSET: Vec3 = Vec3::new(0.0, 2.401, -0.505);
+const SIGIL_SCALE: Vec3 = Vec3::new(0.7313, 0.7313, 1.0);
+
+/// Where a monolith can stand, inside a pyramid or in the open
+struct Site {
+    pyramid: Option<Entity>,
+    transform: Transform,
+}
+
+fn place_monoliths(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    count: Res<MonolithCount>,
+    tp: Res<TerrainParams>,
+    terrain: Res<TerrainSampler>,
+    q_pyramids: Query<(Entity, &Transform), With<Pyramid>>,
+    q_scenes: Query<&Transform, Or<(With<Pyramid>, With<Shelter>)>>,
+) {
+    // Not on the same spots as the batteries buried in dunes
+    let mut rng = StdRng::seed_from_u64(tp.seed() as u64 + 1);
+
+    // Sorted, since pyramids are spawned in no particular order and a seed should pick the same ones
+    let mut pyramids = q_pyramids.iter().collect::<Vec<_>>();
+    pyramids.sort_by(|(_, a), (_, b)| {
+        let (a, b) = (a.translation, b.translation);
+        a.x.total_cmp(&b.x).then(a.z.total_cmp(&b.z))
+    });
+    let mut pyramids = pyramids
+        .into_iter()
+        .map(|(e, _)| Site {
+            pyramid: Some(e),
+            transform: Transform::from_translation(monolith_spot() + Vec3::Y),
+        })
+        .collect::<Vec<_>>();
+    pyramids.shuffle(&mut rng);
+
+    let scenes = q_scenes
+        .iter()
+        .map(|tr| tr.translation.xz())
+        .collect::<Vec<_>>();
+    let center = Vec2::splat(MONOLITH_REGION / 2.0);
+    let spots = poisson_disc_sampling_with(
+        &mut rng,
+        MONOLITH_SPACING,
+        MONOLITH_REGION,
+        usize::MAX,
+        vec![center],
+    );
+    let mut open = spots
+        .into_iter()
+        .map(|p| p - center)
+        .filter(|p| {
+            scenes
+                .iter()
+                .all(|scene| scene.distance(*p) > SCENE_CLEARANCE)
+        })
+        .take(count.0)
+        .map(|p| Site {
+            pyramid: None,
+            transform: Transform::from_translation(
+                p.extend(terrain.height(p) + MONOLITH_ELEVATION).xzy(),
+            ),
+        })
+        .collect::<Vec<_>>();
+    // The last ones are taken first
+    open.reverse();
+
+    // Taken from pyramids and the open in turn
+    let mut sites = [pyramids, open];
+    let mut picked = Vec::new();
+    while picked.len() < count.0 && sites.iter().any(|site| !site.is_empty()) {
+        picked.extend(sites.iter_mut().filter_map(|site| site.pop()));
+    }
+    picked.truncate(count.0);
+    if picked.len() < count.0 {
+        warn!(
+            "only {} of {} monoliths could be placed",
+            picked.len(),
+            count.0
+        );
+    }
+
+    for site in picked {
+        let mut monolith = cmds.spawn((
+            Name::new("Monolith"),
+            Monolith,
+            MapLandmark(LandmarkKind::Monolith),
+            PbrBundle {
+                mesh: asset_server.load("levels/Scene.glb#Mesh7/Primitive0"),
+                material: asset_server.load("levels/Scene.glb#Material1"),
+                transform: site
+                    .transform
+                    .with_rotation(Quat::from_rotation_y(rng.r#gen::<f32>() * TAU))
+                    .with_scale(MONOLITH_SCALE),
+                ..default()
+            },
+        ));
+        if let Some(pyramid) = site.pyramid {
+            monolith.set_parent(pyramid);
+        }
+        monolith.with_children(|parent| {
+            parent.spawn((
+                Name::new("Sigil.Mesh"),
+                PbrBundle {
+                    mesh: asset_server.load("levels/Scene.glb#Mesh6/Primitive0"),
+                    material: asset_server.load("levels/Scene.glb#Material0"),
+                    transform: Transform::from_translation(SIGIL_OFFSET)
+                        .with_rotation(Quat::from_rotation_x(FRAC_PI_2))
+                        .with_scale(SIGIL_SCALE),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
@@ -2,7 +2,10 @@ use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 use bevy_kira_audio::prelude::*;
 
 use crate::{
-    menu::styling::{central_panel, default_text, opaque_root, PADDING},
+    menu::{
+        navigation::{ButtonActivated, MenuNavigationSet},
+        styling::{central_panel, default_text, opaque_root, PADDING},
+    },
     sandstorm::SandstormIntensity,
 };
 
@@ -12,7 +15,7 @@ pub struct WonPlugin;
 impl Plugin for WonPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(GameState::Won), setup_win)
-            .add_systems(Update, interact_restart);
+            .add_systems(Update, interact_restart.after(MenuNavigationSet));
     }
 }
 
@@ -45,10 +48,11 @@ fn setup_win(mut cmds: Commands, asset_server: Res<AssetServer>, audio: Res<Audi
 
 pub fn interact_restart(
     mut cmds: Commands,
-    q_button: Query<&Interaction, (Changed<Interaction>, With<Restart>)>,
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<Restart>>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if let Ok(Interaction::Pressed) = q_button.get_single() {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
         cmds.add(super::checkpoint::load_checkpoint);
         next_state.set(GameState::InCycle);
     }
@@ -53,18 +53,32 @@ pub enum Action {
     Left,
     Right,
     Move,
+    /// Mouse look, moves the view by the motion of each frame
     View,
+    /// Stick look, turns the view at a rate
+    ViewStick,
     Jump,
     Crouch,
     PlaceBeacon,
     Interact,
+    Map,
+    DropBattery,
+    BeaconColor,
+    LabelBeacon,
+}
+
+impl Action {
+    /// Axis actions are bound to sticks, mouse motion or virtual d-pads rather than single buttons
+    pub fn is_dual_axis(&self) -> bool {
+        matches!(self, Action::Move | Action::View | Action::ViewStick)
+    }
 }
 
 fn reset(mut inputs: ResMut<Inputs>) {
     *inputs = default();
 }
 
-fn update(action: Res<ActionState<Action>>, mut inputs: ResMut<Inputs>) {
+fn update(time: Res<Time>, action: Res<ActionState<Action>>, mut inputs: ResMut<Inputs>) {
     if action.pressed(&Action::Forward) {
         inputs.dir += Vec2::Y;
     }
@@ -78,9 +92,22 @@ fn update(action: Res<ActionState<Action>>, mut inputs: ResMut<Inputs>) {
         inputs.dir += Vec2::X;
     }
 
+    if let Some(axis) = action.clamped_axis_pair(&Action::Move) {
+        inputs.dir += axis.xy();
+    }
+
     inputs.dir = inputs.dir.normalize_or_zero();
 
-    inputs.view = action.clamped_axis_pair(&Action::View).unwrap().xy();
+    let mouse_view = action
+        .clamped_axis_pair(&Action::View)
+        .map(|axis| axis.xy())
+        .unwrap_or_default();
+    // In radians per second, unclamped since the sensitivity brings it past 1
+    let stick_view = action
+        .axis_pair(&Action::ViewStick)
+        .map(|axis| axis.xy())
+        .unwrap_or_default();
+    inputs.view = mouse_view + stick_view * time.delta_seconds();
 
     inputs.jump = action.pressed(&Action::Jump);
     inputs.crouch = action.pressed(&Action::Crouch);
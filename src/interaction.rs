@@ -0,0 +1,178 @@
+use avian3d::{
+    prelude::Sensor,
+    spatial_query::{SpatialQuery, SpatialQueryFilter},
+};
+use bevy::prelude::*;
+use leafwing_input_manager::common_conditions::action_just_pressed;
+
+use crate::{
+    camera::{CameraRange, MainCamera},
+    input::Action,
+    player::Player,
+};
+
+/// Finds what the camera points at, shows its prompt and sends [`Interacted`] when the player
+/// interacts with it
+pub struct InteractionPlugin;
+impl Plugin for InteractionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Interactable>()
+            .register_type::<PointingAt>()
+            .init_resource::<PointingAt>()
+            .add_event::<Interacted>()
+            .add_systems(Startup, spawn_prompt)
+            .add_systems(
+                Update,
+                (
+                    raycast_interactables,
+                    interact.run_if(action_just_pressed(Action::Interact)),
+                    update_prompt,
+                )
+                    .chain()
+                    .in_set(InteractionSet),
+            );
+    }
+}
+
+/// Systems reading [`Interacted`] run after this set
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct InteractionSet;
+
+/// How far from the camera interactables can be reached by default
+pub const INTERACT_RANGE: f32 = 8.0;
+
+/// Something the player can point at and interact with, the collider hit can be on a descendant
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Interactable {
+    /// Shown while pointing at it, `<interact>` stands for the interact binding
+    pub prompt: String,
+    /// Distance from the camera it can be reached from
+    pub range: f32,
+}
+
+impl Interactable {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+            range: INTERACT_RANGE,
+        }
+    }
+
+    pub fn with_range(mut self, range: f32) -> Self {
+        self.range = range;
+        self
+    }
+}
+
+/// Interactable the camera points at, within its range
+#[derive(PartialEq, Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct PointingAt(pub Option<Entity>);
+
+/// Sent when the player interacts with an [`Interactable`]
+#[derive(Event)]
+pub struct Interacted(pub Entity);
+
+#[derive(Component)]
+struct InteractPrompt;
+
+fn raycast_interactables(
+    mut pointing_at: ResMut<PointingAt>,
+    q_camera: Query<(&GlobalTransform, &CameraRange), With<MainCamera>>,
+    q_player: Query<Entity, With<Player>>,
+    q_children: Query<&Children>,
+    q_parents: Query<&Parent>,
+    q_interactables: Query<&Interactable>,
+    q_sensors: Query<(), With<Sensor>>,
+    spatial_query: SpatialQuery,
+) {
+    let (Ok(player_e), Ok((cam_tr, range))) = (q_player.get_single(), q_camera.get_single()) else {
+        pointing_at.set_if_neq(PointingAt(None));
+        return;
+    };
+
+    // The hit collider or its closest ancestor that can be interacted with
+    let interactable = |e: Entity| {
+        std::iter::once(e)
+            .chain(q_parents.iter_ancestors(e))
+            .find(|e| q_interactables.contains(*e))
+    };
+    let excluded = std::iter::once(player_e).chain(q_children.iter_descendants(player_e));
+    let hit = spatial_query.cast_ray_predicate(
+        cam_tr.translation(),
+        cam_tr.forward(),
+        range.0,
+        true,
+        SpatialQueryFilter::from_excluded_entities(excluded),
+        // Sensors like safe zones don't block the view
+        &|e| !q_sensors.contains(e) || interactable(e).is_some(),
+    );
+    let target = hit.and_then(|hit| {
+        let e = interactable(hit.entity)?;
+        let reachable = hit.time_of_impact <= q_interactables.get(e).ok()?.range;
+        reachable.then_some(e)
+    });
+    pointing_at.set_if_neq(PointingAt(target));
+}
+
+fn interact(pointing_at: Res<PointingAt>, mut ev_interacted: EventWriter<Interacted>) {
+    if let Some(e) = pointing_at.0 {
+        ev_interacted.send(Interacted(e));
+    }
+}
+
+fn spawn_prompt(mut cmds: Commands, asset_server: Res<AssetServer>) {
+    cmds.spawn((
+        Name::new("Interact prompt"),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                align_items: AlignItems::End,
+                justify_content: JustifyContent::Center,
+                padding: UiRect::all(Val::Percent(5.0)),
+                ..default()
+            },
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent.spawn((
+            InteractPrompt,
+            TextBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: Color::srgb(0.9, 0.9, 0.9),
+                    },
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    });
+}
+
+fn update_prompt(
+    pointing_at: Res<PointingAt>,
+    q_interactables: Query<&Interactable>,
+    mut q_prompt: Query<(&mut Text, &mut Visibility), With<InteractPrompt>>,
+) {
+    let Ok((mut text, mut visibility)) = q_prompt.get_single_mut() else {
+        return;
+    };
+    match pointing_at.0.and_then(|e| q_interactables.get(e).ok()) {
+        Some(interactable) => {
+            if text.sections[0].value != interactable.prompt {
+                text.sections[0].value.clone_from(&interactable.prompt);
+            }
+            visibility.set_if_neq(Visibility::Inherited);
+        }
+        None => {
+            visibility.set_if_neq(Visibility::Hidden);
+        }
+    }
+}
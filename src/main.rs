@@ -6,6 +6,8 @@ mod camera;
 mod debug;
 mod game;
 mod input;
+mod interaction;
+mod map;
 mod materials;
 mod menu;
 mod movement;
@@ -16,6 +18,7 @@ mod settings;
 mod shelter;
 mod terrain;
 mod tower;
+mod tween;
 mod util;
 
 use avian3d::prelude::*;
@@ -56,7 +59,13 @@ fn main() {
                 shelter::ShelterPlugin,
                 battery::BatteryPlugin,
             ),
-            (materials::BuiltinMaterialsPlugin, pyramids::PyramidPlugin),
+            (
+                materials::BuiltinMaterialsPlugin,
+                pyramids::PyramidPlugin,
+                map::MapPlugin,
+                interaction::InteractionPlugin,
+                tween::TweenPlugin,
+            ),
         ))
         .add_systems(Startup, setup);
 
@@ -0,0 +1,370 @@
+use std::f32::consts::{FRAC_PI_4, PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::{
+    beacon::{glow_range, BeaconMarking},
+    camera::{follow::CameraAngles, CameraMode, MainCamera},
+    menu::MenuState,
+    sandstorm::SandstormIntensity,
+    shelter::ShelterSafeZone,
+    tower::TowerBell,
+};
+
+use super::{LandmarkKind, MapJournal};
+
+/// Strip at the top of the screen showing the heading, and where beacons, the tower and the
+/// shelters found so far are
+pub struct CompassPlugin;
+impl Plugin for CompassPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CompassTargets>()
+            .add_systems(Startup, spawn_compass)
+            .add_systems(
+                Update,
+                (
+                    show_compass,
+                    update_headings,
+                    (gather_targets, spawn_markers, update_markers).chain(),
+                ),
+            );
+    }
+}
+
+const STRIP_WIDTH: f32 = 640.0;
+const STRIP_HEIGHT: f32 = 60.0;
+const STRIP_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.35);
+// Angle between the left and right ends of the strip, in radians
+const STRIP_FOV: f32 = PI;
+const ICON_SIZE: f32 = 10.0;
+const LABEL_WIDTH: f32 = 60.0;
+// Wide enough for the distance after a beacon label
+const MARKER_WIDTH: f32 = 160.0;
+const HEADINGS: [(&str, f32); 8] = [
+    ("N", 0.0),
+    ("NE", FRAC_PI_4),
+    ("E", 2.0 * FRAC_PI_4),
+    ("SE", 3.0 * FRAC_PI_4),
+    ("S", 4.0 * FRAC_PI_4),
+    ("SW", 5.0 * FRAC_PI_4),
+    ("W", 6.0 * FRAC_PI_4),
+    ("NW", 7.0 * FRAC_PI_4),
+];
+// Markers are shown this close even when the storm hides everything, in meters
+const MIN_MARKER_RANGE: f32 = 150.0;
+// A safe zone belongs to a discovered shelter when it is this close to it, in meters
+const SHELTER_RADIUS: f32 = 50.0;
+
+/// Bearing of a direction on the ground, clockwise from north (-z), in radians
+pub fn bearing(dir: Vec2) -> f32 {
+    dir.x.atan2(-dir.y)
+}
+
+/// Bearing the camera looks toward
+pub fn heading(angles: &CameraAngles) -> f32 {
+    -angles.yaw
+}
+
+/// Position of a bearing on the strip from 0 (left) to 1 (right), if it fits on it
+fn strip_pos(heading: f32, bearing: f32) -> Option<f32> {
+    let relative = (bearing - heading + PI).rem_euclid(TAU) - PI;
+    let x = relative / STRIP_FOV + 0.5;
+    (0.0..=1.0).contains(&x).then_some(x)
+}
+
+/// Markers are fully visible up to half their range, and gone beyond it
+fn marker_alpha(dist: f32, range: f32) -> f32 {
+    ((range - dist) / (range * 0.5).max(1.0)).clamp(0.0, 1.0)
+}
+
+#[derive(Component)]
+struct Compass;
+
+#[derive(Component)]
+struct CompassStrip;
+
+/// Label of a bearing, in radians
+#[derive(Component)]
+struct CompassHeading(f32);
+
+/// Shows the target at that index of [`CompassTargets`], with an icon and the distance
+#[derive(Component)]
+struct CompassMarker(usize);
+
+/// What the compass points to
+#[derive(Resource, Default)]
+struct CompassTargets(Vec<CompassTarget>);
+
+struct CompassTarget {
+    pos: Vec3,
+    color: Color,
+    label: Option<String>,
+    /// Beyond it the marker is hidden
+    range: f32,
+}
+
+fn spawn_compass(mut cmds: Commands, asset_server: Res<AssetServer>) {
+    let text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 20.0,
+        color: Color::srgb(0.9, 0.9, 0.9),
+    };
+    let centered = |left: f32, width: f32| Style {
+        position_type: PositionType::Absolute,
+        left: Val::Percent(left),
+        width: Val::Px(width),
+        margin: UiRect::left(Val::Px(-width / 2.0)),
+        justify_content: JustifyContent::Center,
+        ..default()
+    };
+    cmds.spawn((
+        Name::new("Compass"),
+        Compass,
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                padding: UiRect::top(Val::Px(16.0)),
+                ..default()
+            },
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent
+            .spawn((
+                CompassStrip,
+                NodeBundle {
+                    style: Style {
+                        width: Val::Px(STRIP_WIDTH),
+                        height: Val::Px(STRIP_HEIGHT),
+                        overflow: Overflow::clip(),
+                        ..default()
+                    },
+                    background_color: STRIP_COLOR.into(),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                // Where the camera looks
+                parent.spawn(NodeBundle {
+                    style: Style {
+                        height: Val::Percent(100.0),
+                        ..centered(50.0, 2.0)
+                    },
+                    background_color: Color::srgba(1.0, 1.0, 1.0, 0.5).into(),
+                    ..default()
+                });
+                for (label, bearing) in HEADINGS {
+                    let font_size = match label.len() {
+                        1 => 24.0,
+                        _ => 16.0,
+                    };
+                    parent.spawn((
+                        CompassHeading(bearing),
+                        TextBundle {
+                            style: Style {
+                                top: Val::Px(4.0),
+                                ..centered(50.0, LABEL_WIDTH)
+                            },
+                            text: Text::from_section(
+                                label,
+                                TextStyle {
+                                    font_size,
+                                    ..text_style.clone()
+                                },
+                            )
+                            .with_justify(JustifyText::Center),
+                            ..default()
+                        },
+                    ));
+                }
+            });
+    });
+}
+
+/// Only while the player is in control, outside of menus
+fn show_compass(
+    camera_mode: Res<CameraMode>,
+    menu_state: Res<State<MenuState>>,
+    mut q_compass: Query<&mut Visibility, With<Compass>>,
+) {
+    let shown = matches!(*camera_mode, CameraMode::Control(_)) && *menu_state == MenuState::None;
+    for mut visibility in &mut q_compass {
+        visibility.set_if_neq(match shown {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        });
+    }
+}
+
+fn update_headings(
+    q_camera: Query<&CameraAngles, With<MainCamera>>,
+    mut q_headings: Query<(&mut Style, &mut Visibility, &CompassHeading)>,
+) {
+    let Ok(angles) = q_camera.get_single() else {
+        return;
+    };
+    for (mut style, mut visibility, heading_label) in &mut q_headings {
+        match strip_pos(heading(angles), heading_label.0) {
+            Some(x) => {
+                style.left = Val::Percent(x * 100.0);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn gather_targets(
+    q_beacons: Query<(&GlobalTransform, &BeaconMarking)>,
+    q_bells: Query<&GlobalTransform, With<TowerBell>>,
+    q_safe_zones: Query<&GlobalTransform, With<ShelterSafeZone>>,
+    journal: Res<MapJournal>,
+    intensity: Res<SandstormIntensity>,
+    mut targets: ResMut<CompassTargets>,
+) {
+    targets.0.clear();
+    let range = intensity.visibility().max(MIN_MARKER_RANGE);
+    // Beacons are seen as far as their halos
+    let beacons = q_beacons.iter().map(|(tr, marking)| CompassTarget {
+        pos: tr.translation(),
+        color: marking.color.color(),
+        label: marking.label.clone(),
+        range: glow_range(intensity.0).max(range),
+    });
+    let landmark = |pos, kind: LandmarkKind| CompassTarget {
+        pos,
+        color: kind.color(),
+        label: None,
+        range,
+    };
+    let bells = q_bells
+        .iter()
+        .map(|tr| landmark(tr.translation(), LandmarkKind::Tower));
+    let known_shelters = q_safe_zones
+        .iter()
+        .map(|tr| tr.translation())
+        .filter(|pos| {
+            journal.landmarks.iter().any(|landmark| {
+                landmark.kind == LandmarkKind::Shelter
+                    && landmark.pos.distance(pos.xz()) < SHELTER_RADIUS
+            })
+        })
+        .map(|pos| landmark(pos, LandmarkKind::Shelter));
+    targets.0.extend(beacons.chain(bells).chain(known_shelters));
+}
+
+/// Markers are kept around when there are fewer targets, hidden
+fn spawn_markers(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    targets: Res<CompassTargets>,
+    q_strip: Query<Entity, With<CompassStrip>>,
+    q_markers: Query<(), With<CompassMarker>>,
+) {
+    let Ok(strip) = q_strip.get_single() else {
+        return;
+    };
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    cmds.entity(strip).with_children(|parent| {
+        for i in q_markers.iter().count()..targets.0.len() {
+            parent
+                .spawn((
+                    CompassMarker(i),
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            bottom: Val::Px(4.0),
+                            width: Val::Px(MARKER_WIDTH),
+                            margin: UiRect::left(Val::Px(-MARKER_WIDTH / 2.0)),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(ICON_SIZE),
+                            height: Val::Px(ICON_SIZE),
+                            ..default()
+                        },
+                        ..default()
+                    });
+                    parent.spawn(TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+        }
+    });
+}
+
+fn update_markers(
+    q_camera: Query<(&CameraAngles, &GlobalTransform), With<MainCamera>>,
+    targets: Res<CompassTargets>,
+    mut q_markers: Query<(&mut Style, &mut Visibility, &Children, &CompassMarker)>,
+    mut q_icons: Query<&mut BackgroundColor>,
+    mut q_texts: Query<&mut Text>,
+) {
+    let Ok((angles, camera_tr)) = q_camera.get_single() else {
+        return;
+    };
+    let camera_pos = camera_tr.translation();
+    for (mut style, mut visibility, children, marker) in &mut q_markers {
+        let shown = targets.0.get(marker.0).and_then(|target| {
+            let x = strip_pos(heading(angles), bearing((target.pos - camera_pos).xz()))?;
+            let dist = target.pos.distance(camera_pos);
+            let alpha = marker_alpha(dist, target.range);
+            (alpha > 0.0).then_some((x, dist, target.color.with_alpha(alpha), &target.label))
+        });
+        let Some((x, dist, color, label)) = shown else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        style.left = Val::Percent(x * 100.0);
+        if let Ok(mut icon) = q_icons.get_mut(children[0]) {
+            *icon = color.into();
+        }
+        if let Ok(mut text) = q_texts.get_mut(children[1]) {
+            let section = &mut text.sections[0];
+            section.value = match label {
+                Some(label) => format!("{label} {dist:.0} m"),
+                None => format!("{dist:.0} m"),
+            };
+            section.style.color = Color::WHITE.with_alpha(color.alpha());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    use bevy::prelude::*;
+
+    use super::{bearing, strip_pos};
+
+    #[test]
+    fn strip_positions() {
+        assert_eq!(bearing(Vec2::NEG_Y), 0.0);
+        assert!((bearing(Vec2::X) - FRAC_PI_2).abs() < 1e-6);
+
+        assert_eq!(strip_pos(0.0, 0.0), Some(0.5));
+        assert_eq!(strip_pos(0.0, FRAC_PI_2), Some(1.0));
+        assert_eq!(strip_pos(0.0, PI * 0.75), None);
+        // Wraps around south
+        let x = strip_pos(PI - 0.1, -PI + 0.1).unwrap();
+        assert!((x - (0.5 + 0.2 / PI)).abs() < 1e-4);
+    }
+}
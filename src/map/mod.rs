@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+
+use crate::{player::Player, terrain::PlacementSet};
+
+mod compass;
+mod render;
+mod ui;
+
+pub struct MapPlugin;
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((ui::MapScreenPlugin, compass::CompassPlugin))
+            .register_type::<MapJournal>()
+            .register_type::<MapFog>()
+            .init_resource::<MapJournal>()
+            .init_resource::<MapFog>()
+            .add_systems(Update, (reveal_fog, discover_landmarks))
+            .add_systems(Update, forget_world.in_set(PlacementSet::Clear));
+    }
+}
+
+/// Side of the square of the world covered by the map, centered on the origin, in meters
+pub const MAP_EXTENT: f32 = 6000.0;
+// Cells of the fog of war along each side of the map
+const FOG_RESOLUTION: usize = 128;
+/// The player uncovers the map and discovers landmarks within this distance
+const REVEAL_RADIUS: f32 = 200.0;
+// Distance the player travels before the fog is revealed again
+const REVEAL_STEP: f32 = 10.0;
+
+/// Position on the map from 0 to 1, from the top left (-x, -z) corner
+pub fn map_uv(pos: Vec2) -> Vec2 {
+    pos / MAP_EXTENT + 0.5
+}
+
+pub fn world_pos(uv: Vec2) -> Vec2 {
+    (uv - 0.5) * MAP_EXTENT
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+pub enum LandmarkKind {
+    Shelter,
+    Pyramid,
+    Tower,
+    Beacon,
+    Monolith,
+    /// Revealed by a sigil
+    Battery,
+}
+
+/// Shown on the map once the player has been close to it
+#[derive(Component)]
+pub struct MapLandmark(pub LandmarkKind);
+
+#[derive(Clone, Copy, Debug, Reflect)]
+pub struct Landmark {
+    pub entity: Entity,
+    pub kind: LandmarkKind,
+    pub pos: Vec2,
+}
+
+/// What the player knows of the world, kept in checkpoints
+#[derive(Resource, Reflect, Clone, Default)]
+#[reflect(Resource)]
+pub struct MapJournal {
+    pub landmarks: Vec<Landmark>,
+    /// Placed by the player on the map
+    pub waypoints: Vec<Vec2>,
+}
+
+/// Parts of the map the player has uncovered, kept in checkpoints
+#[derive(Resource, Reflect, Clone)]
+#[reflect(Resource)]
+pub struct MapFog {
+    /// Rows from the top of the map, 0 is hidden and 255 uncovered
+    cells: Vec<u8>,
+}
+
+impl Default for MapFog {
+    fn default() -> Self {
+        Self {
+            cells: vec![0; FOG_RESOLUTION * FOG_RESOLUTION],
+        }
+    }
+}
+
+impl MapFog {
+    /// Uncovers a disc, its edge fades over the outer third
+    pub fn reveal(&mut self, pos: Vec2, radius: f32) {
+        let cell_size = MAP_EXTENT / FOG_RESOLUTION as f32;
+        let to_cell = |pos: Vec2| map_uv(pos) * FOG_RESOLUTION as f32;
+        let min = to_cell(pos - radius).floor().max(Vec2::ZERO).as_uvec2();
+        let max = to_cell(pos + radius)
+            .ceil()
+            .min(Vec2::splat(FOG_RESOLUTION as f32))
+            .as_uvec2();
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let center = world_pos((UVec2::new(x, y).as_vec2() + 0.5) / FOG_RESOLUTION as f32);
+                let dist = (center.distance(pos) - cell_size / 2.0).max(0.0) / radius;
+                let uncovered = ((1.0 - dist) * 3.0).clamp(0.0, 1.0);
+                let cell = &mut self.cells[y as usize * FOG_RESOLUTION + x as usize];
+                *cell = (*cell).max((uncovered * 255.0) as u8);
+            }
+        }
+    }
+
+    /// How much of the cell at `pos` is uncovered, from 0 to 1
+    pub fn revealed(&self, pos: Vec2) -> f32 {
+        let cell = (map_uv(pos) * FOG_RESOLUTION as f32).floor().as_ivec2();
+        if cell.min_element() < 0 || cell.max_element() >= FOG_RESOLUTION as i32 {
+            return 0.0;
+        }
+        self.cells[cell.y as usize * FOG_RESOLUTION + cell.x as usize] as f32 / 255.0
+    }
+}
+
+/// A new world is unexplored
+fn forget_world(mut cmds: Commands) {
+    cmds.insert_resource(MapJournal::default());
+    cmds.insert_resource(MapFog::default());
+}
+
+fn reveal_fog(
+    q_player: Query<&GlobalTransform, With<Player>>,
+    mut fog: ResMut<MapFog>,
+    mut last_pos: Local<Option<Vec2>>,
+) {
+    let Ok(tr) = q_player.get_single() else {
+        return;
+    };
+    let pos = tr.translation().xz();
+    if last_pos.is_some_and(|last| last.distance(pos) < REVEAL_STEP) {
+        return;
+    }
+    *last_pos = Some(pos);
+    fog.reveal(pos, REVEAL_RADIUS);
+}
+
+fn discover_landmarks(
+    q_player: Query<&GlobalTransform, With<Player>>,
+    q_landmarks: Query<(Entity, &GlobalTransform, &MapLandmark)>,
+    mut journal: ResMut<MapJournal>,
+) {
+    let Ok(player_tr) = q_player.get_single() else {
+        return;
+    };
+    let player_pos = player_tr.translation().xz();
+    for (e, tr, landmark) in &q_landmarks {
+        let pos = tr.translation().xz();
+        if pos.distance(player_pos) > REVEAL_RADIUS
+            || journal.landmarks.iter().any(|known| known.entity == e)
+        {
+            continue;
+        }
+        journal.landmarks.push(Landmark {
+            entity: e,
+            kind: landmark.0,
+            pos,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{map_uv, world_pos, MapFog, MAP_EXTENT};
+
+    #[test]
+    fn uv_round_trip() {
+        assert_eq!(map_uv(Vec2::ZERO), Vec2::splat(0.5));
+        assert_eq!(map_uv(Vec2::splat(-MAP_EXTENT / 2.0)), Vec2::ZERO);
+        let pos = Vec2::new(1234.0, -567.0);
+        assert!(world_pos(map_uv(pos)).distance(pos) < 1e-3);
+    }
+
+    #[test]
+    fn fog() {
+        let mut fog = MapFog::default();
+        let pos = Vec2::new(-800.0, 300.0);
+        assert_eq!(fog.revealed(pos), 0.0);
+
+        fog.reveal(pos, 200.0);
+        assert_eq!(fog.revealed(pos), 1.0);
+        assert_eq!(fog.revealed(pos + Vec2::X * 100.0), 1.0);
+        assert_eq!(fog.revealed(pos + Vec2::X * 300.0), 0.0);
+
+        // Revealing doesn't cover anything again, and the map edges are safe
+        fog.reveal(pos + Vec2::X * 150.0, 50.0);
+        assert_eq!(fog.revealed(pos), 1.0);
+        fog.reveal(Vec2::splat(MAP_EXTENT / 2.0), 200.0);
+        assert_eq!(fog.revealed(Vec2::splat(MAP_EXTENT)), 0.0);
+    }
+}
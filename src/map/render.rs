@@ -0,0 +1,77 @@
+use bevy::{
+    prelude::*,
+    render::{
+        render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+    },
+    tasks::ComputeTaskPool,
+};
+
+use crate::terrain::TerrainSampler;
+
+use super::{world_pos, MapFog, FOG_RESOLUTION};
+
+// Pixels of the terrain image along each side of the map
+const TERRAIN_RESOLUTION: usize = 256;
+
+// Same as the sand shader, in linear space
+const SAND_COLOR: Vec3 = Vec3::new(0.88, 0.66, 0.07);
+const FOG_COLOR: [u8; 3] = [24, 20, 16];
+// Direction the map is lit from, from the top left like most maps
+const LIGHT_DIR: Vec3 = Vec3::new(-1.0, 1.5, -1.0);
+
+fn image(resolution: usize, data: Vec<u8>, format: TextureFormat) -> Image {
+    Image::new(
+        Extent3d {
+            width: resolution as u32,
+            height: resolution as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        format,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// Color of the ground seen from above, shaded by its slope
+fn terrain_color(sampler: &TerrainSampler, pos: Vec2) -> [u8; 4] {
+    let tint = sampler.tint(pos);
+    let color = SAND_COLOR.lerp(Vec3::new(tint.red, tint.green, tint.blue), tint.alpha);
+    let light = sampler.normal(pos).dot(LIGHT_DIR.normalize()).max(0.0);
+    // Valleys a bit darker, so that the relief reads at a glance
+    let depth = (1.0 + sampler.height(pos) / 200.0).clamp(0.7, 1.1);
+    let color = color * (0.35 + 0.75 * light) * depth;
+    let srgb = Srgba::from(LinearRgba::new(color.x, color.y, color.z, 1.0));
+    [srgb.red, srgb.green, srgb.blue, 1.0].map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8)
+}
+
+/// Top-down view of the whole map
+pub fn render_terrain(sampler: &TerrainSampler) -> Image {
+    let mut data = vec![0; TERRAIN_RESOLUTION * TERRAIN_RESOLUTION * 4];
+    ComputeTaskPool::get().scope(|scope| {
+        for (y, row) in data.chunks_mut(TERRAIN_RESOLUTION * 4).enumerate() {
+            scope.spawn(async move {
+                for (x, pixel) in row.chunks_mut(4).enumerate() {
+                    let uv = (UVec2::new(x as u32, y as u32).as_vec2() + 0.5)
+                        / TERRAIN_RESOLUTION as f32;
+                    pixel.copy_from_slice(&terrain_color(sampler, world_pos(uv)));
+                }
+            });
+        }
+    });
+    image(TERRAIN_RESOLUTION, data, TextureFormat::Rgba8UnormSrgb)
+}
+
+/// Covers the parts of the map that haven't been uncovered
+pub fn render_fog(fog: &MapFog) -> Image {
+    let data = fog
+        .cells
+        .iter()
+        .flat_map(|uncovered| {
+            let [r, g, b] = FOG_COLOR;
+            [r, g, b, 255 - uncovered]
+        })
+        .collect();
+    image(FOG_RESOLUTION, data, TextureFormat::Rgba8UnormSrgb)
+}
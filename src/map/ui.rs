@@ -0,0 +1,364 @@
+use bevy::{
+    prelude::*,
+    ui::RelativeCursorPosition,
+    window::{CursorGrabMode, PrimaryWindow},
+};
+use leafwing_input_manager::{action_state::ActionState, common_conditions::action_just_pressed};
+
+use crate::{
+    beacon::BeaconMarking,
+    input::Action,
+    menu::{navigation::MenuAction, styling::default_text, MenuState, MenuToggleSet},
+    player::Player,
+    terrain::TerrainSampler,
+};
+
+use super::{map_uv, render, world_pos, LandmarkKind, MapFog, MapJournal, MAP_EXTENT};
+
+pub struct MapScreenPlugin;
+impl Plugin for MapScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MapImages>()
+            .add_systems(Update, mark_terrain_stale)
+            .add_systems(
+                Update,
+                switch_to_map
+                    .run_if(in_state(MenuState::None).and_then(action_just_pressed(Action::Map))),
+            )
+            .add_systems(
+                Update,
+                (close_map, place_waypoints, update_markers)
+                    .chain()
+                    .after(MenuToggleSet)
+                    .run_if(in_state(MenuState::Map)),
+            )
+            .add_systems(OnEnter(MenuState::Map), (release_cursor, spawn_map))
+            .add_systems(OnExit(MenuState::Map), (grab_cursor, despawn_map));
+    }
+}
+
+const BACKGROUND_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.85);
+// Side of the map on screen, in percents of the smallest side of the window
+const MAP_SIZE: f32 = 80.0;
+const MARKER_SIZE: f32 = 12.0;
+const PLAYER_COLOR: Color = Color::WHITE;
+const WAYPOINT_COLOR: Color = Color::srgb(0.9, 0.3, 0.9);
+// Right clicking this close to a waypoint removes it, in meters
+const WAYPOINT_PICK_RADIUS: f32 = MAP_EXTENT / 50.0;
+
+impl LandmarkKind {
+    pub fn color(self) -> Color {
+        match self {
+            LandmarkKind::Shelter => Color::srgb(0.35, 0.8, 0.4),
+            LandmarkKind::Pyramid => Color::srgb(0.95, 0.75, 0.2),
+            LandmarkKind::Tower => Color::srgb(0.6, 0.8, 1.0),
+            LandmarkKind::Beacon => Color::srgb(1.0, 0.45, 0.2),
+            LandmarkKind::Monolith => Color::srgb(0.75, 0.5, 1.0),
+            LandmarkKind::Battery => Color::srgb(0.3, 0.8, 1.0),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            LandmarkKind::Shelter => "Shelter",
+            LandmarkKind::Pyramid => "Pyramid",
+            LandmarkKind::Tower => "Tower",
+            LandmarkKind::Beacon => "Beacon",
+            LandmarkKind::Monolith => "Monolith",
+            LandmarkKind::Battery => "Battery",
+        }
+    }
+}
+
+/// The terrain is only rendered again when the map is opened after it changed
+#[derive(Resource)]
+struct MapImages {
+    terrain: Handle<Image>,
+    fog: Handle<Image>,
+    stale: bool,
+}
+
+impl FromWorld for MapImages {
+    fn from_world(world: &mut World) -> Self {
+        let mut images = world.resource_mut::<Assets<Image>>();
+        Self {
+            terrain: images.add(Image::default()),
+            fog: images.add(Image::default()),
+            stale: true,
+        }
+    }
+}
+
+fn mark_terrain_stale(sampler: Res<TerrainSampler>, mut map_images: ResMut<MapImages>) {
+    if sampler.is_changed() {
+        map_images.stale = true;
+    }
+}
+
+#[derive(Component)]
+struct MapScreen;
+
+/// The map itself, placing waypoints where it is clicked
+#[derive(Component)]
+struct MapArea;
+
+#[derive(Component)]
+struct MapMarkers;
+
+fn switch_to_map(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Map);
+}
+
+fn close_map(
+    actions: Res<ActionState<Action>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if actions.just_pressed(&Action::Map) || menu_actions.just_pressed(&MenuAction::Back) {
+        menu_state.set(MenuState::None);
+    }
+}
+
+fn release_cursor(mut q_window: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = q_window.get_single_mut() {
+        window.cursor.grab_mode = CursorGrabMode::None;
+        window.cursor.visible = true;
+    }
+}
+
+/// The map always closes back to the game
+fn grab_cursor(mut q_window: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = q_window.get_single_mut() {
+        window.cursor.grab_mode = CursorGrabMode::Locked;
+        window.cursor.visible = false;
+    }
+}
+
+fn spawn_map(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut map_images: ResMut<MapImages>,
+    sampler: Res<TerrainSampler>,
+    fog: Res<MapFog>,
+) {
+    if map_images.stale {
+        images.insert(&map_images.terrain, render::render_terrain(&sampler));
+        map_images.stale = false;
+    }
+    images.insert(&map_images.fog, render::render_fog(&fog));
+
+    let full = Style {
+        position_type: PositionType::Absolute,
+        width: Val::Percent(100.0),
+        height: Val::Percent(100.0),
+        ..default()
+    };
+    cmds.spawn((
+        Name::new("Map"),
+        MapScreen,
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            background_color: BACKGROUND_COLOR.into(),
+            ..default()
+        },
+    ))
+    .with_children(|parent| {
+        parent
+            .spawn((
+                MapArea,
+                RelativeCursorPosition::default(),
+                ImageBundle {
+                    style: Style {
+                        width: Val::VMin(MAP_SIZE),
+                        height: Val::VMin(MAP_SIZE),
+                        ..default()
+                    },
+                    image: UiImage::new(map_images.terrain.clone()),
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                parent.spawn(ImageBundle {
+                    style: full.clone(),
+                    image: UiImage::new(map_images.fog.clone()),
+                    ..default()
+                });
+                parent.spawn((
+                    MapMarkers,
+                    NodeBundle {
+                        style: full,
+                        ..default()
+                    },
+                ));
+            });
+
+        // Legend
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|parent| {
+                let kinds = [
+                    LandmarkKind::Tower,
+                    LandmarkKind::Shelter,
+                    LandmarkKind::Pyramid,
+                    LandmarkKind::Monolith,
+                    LandmarkKind::Battery,
+                    LandmarkKind::Beacon,
+                ];
+                let entries = kinds
+                    .map(|kind| (kind.color(), kind.label()))
+                    .into_iter()
+                    .chain([(WAYPOINT_COLOR, "Waypoint"), (PLAYER_COLOR, "You")]);
+                for (color, label) in entries {
+                    parent.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(MARKER_SIZE),
+                            height: Val::Px(MARKER_SIZE),
+                            ..default()
+                        },
+                        background_color: color.into(),
+                        ..default()
+                    });
+                    parent.spawn(default_text(label, 20.0, &asset_server));
+                }
+            });
+        parent.spawn(default_text(
+            "Left click places a waypoint, right click removes it",
+            20.0,
+            &asset_server,
+        ));
+    });
+}
+
+fn despawn_map(mut cmds: Commands, q_map: Query<Entity, With<MapScreen>>) {
+    for e in &q_map {
+        cmds.entity(e).despawn_recursive();
+    }
+}
+
+fn place_waypoints(
+    buttons: Res<ButtonInput<MouseButton>>,
+    q_area: Query<&RelativeCursorPosition, With<MapArea>>,
+    mut journal: ResMut<MapJournal>,
+) {
+    let Ok(cursor) = q_area.get_single() else {
+        return;
+    };
+    let Some(uv) = cursor.normalized.filter(|_| cursor.mouse_over()) else {
+        return;
+    };
+    let pos = world_pos(uv);
+    if buttons.just_pressed(MouseButton::Left) {
+        journal.waypoints.push(pos);
+    } else if buttons.just_pressed(MouseButton::Right) {
+        let closest = journal
+            .waypoints
+            .iter()
+            .enumerate()
+            .map(|(i, waypoint)| (i, waypoint.distance(pos)))
+            .filter(|(_, dist)| *dist < WAYPOINT_PICK_RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b));
+        if let Some((i, _)) = closest {
+            journal.waypoints.remove(i);
+        }
+    }
+}
+
+/// The label is written to the right of the marker
+fn spawn_marker(
+    parent: &mut ChildBuilder,
+    pos: Vec2,
+    color: Color,
+    label: Option<(&str, &AssetServer)>,
+) {
+    let uv = map_uv(pos);
+    if uv.min_element() < 0.0 || uv.max_element() > 1.0 {
+        return;
+    }
+    let mut marker = parent.spawn(NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            left: Val::Percent(uv.x * 100.0),
+            top: Val::Percent(uv.y * 100.0),
+            width: Val::Px(MARKER_SIZE),
+            height: Val::Px(MARKER_SIZE),
+            // Centered on the position
+            margin: UiRect {
+                left: Val::Px(-MARKER_SIZE / 2.0),
+                top: Val::Px(-MARKER_SIZE / 2.0),
+                ..default()
+            },
+            ..default()
+        },
+        background_color: color.into(),
+        ..default()
+    });
+    if let Some((label, asset_server)) = label {
+        marker.with_children(|parent| {
+            let mut text = default_text(label, 16.0, asset_server);
+            text.style = Style {
+                position_type: PositionType::Absolute,
+                left: Val::Px(MARKER_SIZE + 4.0),
+                top: Val::Px(-MARKER_SIZE / 2.0),
+                ..default()
+            };
+            parent.spawn(text);
+        });
+    }
+}
+
+fn update_markers(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    q_markers: Query<Entity, With<MapMarkers>>,
+    q_added: Query<(), Added<MapMarkers>>,
+    q_player: Query<&GlobalTransform, With<Player>>,
+    q_beacons: Query<&BeaconMarking>,
+    journal: Res<MapJournal>,
+) {
+    if !journal.is_changed() && q_added.is_empty() {
+        return;
+    }
+    for e in &q_markers {
+        cmds.entity(e)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for landmark in &journal.landmarks {
+                    match q_beacons.get(landmark.entity) {
+                        Ok(marking) => spawn_marker(
+                            parent,
+                            landmark.pos,
+                            marking.color.color(),
+                            marking
+                                .label
+                                .as_deref()
+                                .map(|label| (label, &*asset_server)),
+                        ),
+                        Err(_) => spawn_marker(parent, landmark.pos, landmark.kind.color(), None),
+                    }
+                }
+                for waypoint in &journal.waypoints {
+                    spawn_marker(parent, *waypoint, WAYPOINT_COLOR, None);
+                }
+                if let Ok(tr) = q_player.get_single() {
+                    spawn_marker(parent, tr.translation().xz(), PLAYER_COLOR, None);
+                }
+            });
+    }
+}
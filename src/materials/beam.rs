@@ -0,0 +1,95 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    pbr::NotShadowCaster,
+    prelude::*,
+    reflect::TypePath,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+        render_resource::{AsBindGroup, ShaderRef},
+    },
+};
+
+/// Additive light going up from its base and fading with height, unaffected by fog
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct BeamMaterial {
+    /// Can go above 1 to bloom, the alpha scales the whole beam
+    #[uniform(0)]
+    pub color: LinearRgba,
+}
+
+impl Material for BeamMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/beam.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+}
+
+// Sides around the beam, it is thin enough to look round
+const BEAM_SIDES: usize = 16;
+
+/// Open cylinder of radius and height 1 going up from the origin, shared by every beam
+#[derive(Resource)]
+pub struct BeamMesh(pub Handle<Mesh>);
+
+impl FromWorld for BeamMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        Self(meshes.add(beam_mesh()))
+    }
+}
+
+/// The v texture coordinate goes from 0 at the base to 1 at the top
+fn beam_mesh() -> Mesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..=BEAM_SIDES {
+        let u = i as f32 / BEAM_SIDES as f32;
+        let (sin, cos) = (u * TAU).sin_cos();
+        for v in [0.0, 1.0] {
+            positions.push([cos, v, sin]);
+            normals.push([cos, 0.0, sin]);
+            uvs.push([u, v]);
+        }
+    }
+    let indices = (0..BEAM_SIDES as u32)
+        .flat_map(|i| {
+            let (bottom, top) = (i * 2, i * 2 + 1);
+            let (next_bottom, next_top) = (bottom + 2, top + 2);
+            [bottom, top, next_bottom, next_bottom, top, next_top]
+        })
+        .collect();
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Beam going up from `base`
+pub fn beam_bundle(
+    mesh: &BeamMesh,
+    material: Handle<BeamMaterial>,
+    base: Vec3,
+    radius: f32,
+    height: f32,
+) -> (MaterialMeshBundle<BeamMaterial>, NotShadowCaster) {
+    (
+        MaterialMeshBundle {
+            mesh: mesh.0.clone(),
+            material,
+            transform: Transform::from_translation(base)
+                .with_scale(Vec3::new(radius, height, radius)),
+            ..default()
+        },
+        NotShadowCaster,
+    )
+}
@@ -0,0 +1,24 @@
+use bevy::{
+    prelude::*,
+    reflect::TypePath,
+    render::render_resource::{AsBindGroup, ShaderRef},
+};
+
+/// Additive halo brightest where the surface faces the camera, unaffected by fog. Meant for
+/// spheres, which then look like a soft round light from every side.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct GlowMaterial {
+    /// Can go above 1 to bloom, the alpha scales the whole halo
+    #[uniform(0)]
+    pub color: LinearRgba,
+}
+
+impl Material for GlowMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/glow.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode {
+        AlphaMode::Add
+    }
+}
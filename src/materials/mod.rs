@@ -1,5 +1,7 @@
 use bevy::{asset::load_internal_asset, pbr::ExtendedMaterial, prelude::*};
 
+pub mod beam;
+pub mod glow;
 pub mod sand;
 
 pub const COMMON: Handle<Shader> = Handle::weak_from_u128(2484523442896896);
@@ -21,8 +23,11 @@ impl Plugin for BuiltinMaterialsPlugin {
             "../../assets/shaders/simplex_vec3f.wgsl",
             Shader::from_wgsl
         );
-        app.add_plugins(MaterialPlugin::<
-            ExtendedMaterial<StandardMaterial, sand::SandMaterialExtension>,
-        >::default());
+        app.add_plugins((
+            MaterialPlugin::<ExtendedMaterial<StandardMaterial, sand::SandMaterialExtension>>::default(),
+            MaterialPlugin::<beam::BeamMaterial>::default(),
+            MaterialPlugin::<glow::GlowMaterial>::default(),
+        ))
+        .init_resource::<beam::BeamMesh>();
     }
 }
@@ -1,49 +1,216 @@
 use bevy::prelude::*;
-use leafwing_input_manager::{input_map::InputMap, user_input::UserInput};
+use leafwing_input_manager::{
+    action_state::ActionState,
+    axislike::{DualAxis, VirtualDPad},
+    input_map::InputMap,
+    user_input::UserInput,
+};
 
-use crate::input::Action;
+use crate::{
+    input::Action,
+    menu::navigation::{ButtonActivated, MenuAction},
+    settings::{default_input_map, mouse_view_axis, stick_view_axis, StormAssist, ViewDistance},
+};
 
-use super::{ActionButton, ButtonState, ControlsBack, KeyText, MenuState};
+use super::{
+    ActionButton, ButtonState, ControlsBack, ControlsNewWorld, ControlsReset, KeyText, MenuState,
+    StormAssistButton, StormAssistText, ViewDistanceButton, ViewDistanceText, REBINDABLE_ACTIONS,
+};
+
+// How far a stick has to be pushed while capturing to bind it
+const STICK_CAPTURE_THRESHOLD: f32 = 0.6;
+
+const KEY_TEXT_COLOR: Color = Color::srgb(0.91, 0.83, 0.49);
+const CONFLICT_TEXT_COLOR: Color = Color::srgb(0.91, 0.32, 0.25);
 
 pub fn interact_action_button(
-    mut q_button: Query<(&Interaction, &ActionButton), Changed<Interaction>>,
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<&ActionButton>,
     mut map: ResMut<InputMap<Action>>,
     mut button_state: ResMut<ButtonState>,
 
     keys: Res<ButtonInput<KeyCode>>,
     buttons: Res<ButtonInput<MouseButton>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
 ) {
-    match &mut button_state.0 {
-        Some(action) => {
-            let mut bind: Option<UserInput> = None;
-            for k in keys.get_just_pressed() {
-                bind = Some((*k).into());
+    match button_state.0 {
+        Some(target) => {
+            ev_activated.clear();
+            if keys.just_pressed(KeyCode::Escape) {
+                button_state.0 = None;
+                return;
+            }
+            if keys.just_pressed(KeyCode::Delete) || keys.just_pressed(KeyCode::Backspace) {
+                rebind(&mut map, target, None);
+                button_state.0 = None;
+                return;
             }
 
-            for b in buttons.get_just_pressed() {
-                bind = Some((*b).into());
+            let mut bind: Option<UserInput> = None;
+            if target.action == Action::View {
+                // Moving the mouse while reaching for another input doesn't bind it
+                if buttons.just_pressed(MouseButton::Left) {
+                    bind = Some(mouse_view_axis().into());
+                }
+            } else if target.action.is_dual_axis() {
+                for gamepad in gamepads.iter() {
+                    let stick = |x, y| {
+                        Vec2::new(
+                            gamepad_axes
+                                .get(GamepadAxis::new(gamepad, x))
+                                .unwrap_or_default(),
+                            gamepad_axes
+                                .get(GamepadAxis::new(gamepad, y))
+                                .unwrap_or_default(),
+                        )
+                    };
+                    let left = stick(GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY);
+                    let right = stick(GamepadAxisType::RightStickX, GamepadAxisType::RightStickY);
+                    if left.length() > STICK_CAPTURE_THRESHOLD {
+                        bind = Some(match target.action {
+                            Action::ViewStick => stick_view_axis(DualAxis::left_stick()).into(),
+                            _ => DualAxis::left_stick().into(),
+                        });
+                    } else if right.length() > STICK_CAPTURE_THRESHOLD {
+                        bind = Some(match target.action {
+                            Action::ViewStick => stick_view_axis(DualAxis::right_stick()).into(),
+                            _ => DualAxis::right_stick().into(),
+                        });
+                    }
+                }
+
+                if target.action == Action::Move {
+                    for k in keys.get_just_pressed() {
+                        match k {
+                            KeyCode::KeyW | KeyCode::KeyA | KeyCode::KeyS | KeyCode::KeyD => {
+                                bind = Some(VirtualDPad::wasd().into());
+                            }
+                            KeyCode::ArrowUp
+                            | KeyCode::ArrowLeft
+                            | KeyCode::ArrowDown
+                            | KeyCode::ArrowRight => {
+                                bind = Some(VirtualDPad::arrow_keys().into());
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            } else {
+                for k in keys.get_just_pressed() {
+                    bind = Some((*k).into());
+                }
+
+                for b in buttons.get_just_pressed() {
+                    bind = Some((*b).into());
+                }
+
+                for b in gamepad_buttons.get_just_pressed() {
+                    bind = Some(b.button_type.into());
+                }
             }
 
             if let Some(bind) = bind {
-                map.clear_action(action);
-                map.insert(*action, bind);
+                rebind(&mut map, target, Some(bind));
                 button_state.0 = None;
             }
         }
         _ => {
-            for (interaction, action) in &mut q_button {
-                match *interaction {
-                    Interaction::Pressed => {
-                        // change user action
-                        button_state.0 = Some(action.0);
-                    }
-                    _ => {}
+            for ev in ev_activated.read() {
+                if let Ok(action) = q_button.get(ev.0) {
+                    // change user action
+                    button_state.0 = Some(*action);
                 }
             }
         }
     }
 }
 
+fn bindings(map: &InputMap<Action>, action: Action) -> Vec<UserInput> {
+    map.get(&action).cloned().unwrap_or_default()
+}
+
+fn set_bindings(map: &mut InputMap<Action>, action: Action, inputs: Vec<UserInput>) {
+    map.clear_action(&action);
+    for input in inputs {
+        map.insert(action, input);
+    }
+}
+
+/// Replaces the binding in `target`'s slot, or clears it if `input` is `None`.
+///
+/// If the new input is already used elsewhere, that binding takes the slot's previous input,
+/// so that an input is never bound to two actions at once.
+fn rebind(map: &mut InputMap<Action>, target: ActionButton, input: Option<UserInput>) {
+    let mut inputs = bindings(map, target.action);
+    let previous = inputs.get(target.slot).cloned();
+
+    if let Some(input) = &input {
+        for (other, _) in REBINDABLE_ACTIONS {
+            if other == target.action {
+                continue;
+            }
+            let mut other_inputs = bindings(map, other);
+            let Some(i) = other_inputs.iter().position(|x| x == input) else {
+                continue;
+            };
+            match &previous {
+                Some(previous) if !other_inputs.contains(previous) => {
+                    other_inputs[i] = previous.clone()
+                }
+                _ => {
+                    other_inputs.remove(i);
+                }
+            }
+            set_bindings(map, other, other_inputs);
+        }
+
+        // Already bound to the other slot of this action, swap them
+        if let Some(i) = inputs.iter().position(|x| x == input) {
+            match &previous {
+                Some(previous) => inputs[i] = previous.clone(),
+                None => {
+                    inputs.remove(i);
+                }
+            }
+        }
+    }
+
+    match input {
+        Some(input) if target.slot < inputs.len() => inputs[target.slot] = input,
+        Some(input) => inputs.push(input),
+        None if target.slot < inputs.len() => {
+            inputs.remove(target.slot);
+        }
+        None => {}
+    }
+    set_bindings(map, target.action, inputs);
+}
+
+/// The single inputs `input` is made of, a virtual d-pad presses four buttons
+fn buttons(input: &UserInput) -> Vec<UserInput> {
+    match input {
+        UserInput::VirtualDPad(dpad) => [&dpad.up, &dpad.down, &dpad.left, &dpad.right]
+            .into_iter()
+            .cloned()
+            .map(UserInput::Single)
+            .collect(),
+        _ => vec![input.clone()],
+    }
+}
+
+/// Whether `input` or one of its buttons is also bound to an action other than `action`
+fn is_conflicting(map: &InputMap<Action>, action: Action, input: &UserInput) -> bool {
+    let own = buttons(input);
+    REBINDABLE_ACTIONS
+        .iter()
+        .filter(|(other, _)| *other != action)
+        .filter_map(|(other, _)| map.get(other))
+        .flatten()
+        .any(|other| buttons(other).iter().any(|b| own.contains(b)))
+}
+
 pub fn update_button_text(
     q_action_button: Query<&ActionButton>,
     mut q_text: Query<(&mut Text, &Parent), With<KeyText>>,
@@ -53,30 +220,100 @@ pub fn update_button_text(
     for (mut text, parent) in &mut q_text {
         let action_btn = q_action_button.get(parent.get()).unwrap();
         match button_state.0 {
-            Some(action) if action_btn.0 == action => {
-                text.sections[0].value = String::from("???");
+            Some(target) if target == *action_btn => {
+                text.sections[0].value = String::from(match target.action {
+                    Action::View => "Click to use the mouse",
+                    _ => "???",
+                });
             }
             _ => {
                 let binding = map
-                    .get(&action_btn.0)
-                    .map(|inputs| format!("{}", inputs[0]))
-                    .unwrap_or_default();
-                text.sections[0].value = binding;
+                    .get(&action_btn.action)
+                    .and_then(|inputs| inputs.get(action_btn.slot));
+                text.sections[0].value = binding
+                    .map(|input| format!("{}", input))
+                    .unwrap_or_else(|| String::from("-"));
 
-                text.sections[0].style.color = Color::srgb(0.91, 0.83, 0.49);
+                text.sections[0].style.color = match binding {
+                    Some(input) if is_conflicting(&map, action_btn.action, input) => {
+                        CONFLICT_TEXT_COLOR
+                    }
+                    _ => KEY_TEXT_COLOR,
+                };
             }
         }
     }
 }
 
 pub fn interact_back_button(
-    mut q_button: Query<&Interaction, (Changed<Interaction>, With<ControlsBack>)>,
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<ControlsBack>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    button_state: Res<ButtonState>,
     mut menu_state: ResMut<NextState<MenuState>>,
 ) {
-    if let Ok(interaction) = q_button.get_single_mut() {
-        match *interaction {
-            Interaction::Pressed => menu_state.set(MenuState::None),
-            _ => {}
-        }
+    let back = menu_actions.just_pressed(&MenuAction::Back) && button_state.0.is_none();
+    if back || ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        menu_state.set(MenuState::None);
+    }
+}
+
+pub fn interact_reset_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<ControlsReset>>,
+    mut map: ResMut<InputMap<Action>>,
+    mut button_state: ResMut<ButtonState>,
+) {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        *map = default_input_map();
+        button_state.0 = None;
+    }
+}
+
+pub fn interact_view_distance_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<ViewDistanceButton>>,
+    mut view_distance: ResMut<ViewDistance>,
+) {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        *view_distance = view_distance.next();
+    }
+}
+
+pub fn interact_storm_assist_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<StormAssistButton>>,
+    mut storm_assist: ResMut<StormAssist>,
+) {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        storm_assist.0 = !storm_assist.0;
+    }
+}
+
+pub fn interact_new_world_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<ControlsNewWorld>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        menu_state.set(MenuState::World);
+    }
+}
+
+pub fn update_view_distance_text(
+    mut q_text: Query<&mut Text, With<ViewDistanceText>>,
+    view_distance: Res<ViewDistance>,
+) {
+    for mut text in &mut q_text {
+        text.sections[0].value = format!("View distance: {}", view_distance.label());
+    }
+}
+
+pub fn update_storm_assist_text(
+    mut q_text: Query<&mut Text, With<StormAssistText>>,
+    storm_assist: Res<StormAssist>,
+) {
+    for mut text in &mut q_text {
+        text.sections[0].value = format!("Storm assist: {}", storm_assist.label());
     }
 }
@@ -1,13 +1,18 @@
 use bevy::prelude::*;
 
-use crate::{
-    input::Action,
-    menu::styling::{
-        default_text, ColoredButton, BUTTON_COLOR, BUTTON_STYLE, DEFAULT_BACKGROUND_COLOR,
-    },
+use crate::menu::styling::{
+    default_text, ColoredButton, BUTTON_COLOR, BUTTON_HEIGHT, BUTTON_STYLE,
+    DEFAULT_BACKGROUND_COLOR,
 };
 
-use super::{ActionButton, ControlsBack, ControlsMenu, KeyText};
+use super::{
+    ActionButton, ControlsBack, ControlsMenu, ControlsNewWorld, ControlsReset, KeyText,
+    StormAssistButton, StormAssistText, ViewDistanceButton, ViewDistanceText, BINDING_SLOTS,
+    REBINDABLE_ACTIONS,
+};
+
+const LABEL_WIDTH: f32 = 240.0;
+const BINDING_BUTTON_WIDTH: f32 = 300.0;
 
 pub fn spawn_menu(mut commands: Commands, asset_server: Res<AssetServer>) {
     let _option_menu_entity = build_menu(&mut commands, &asset_server);
@@ -39,67 +44,55 @@ pub fn build_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
             ControlsMenu,
         ))
         .with_children(|parent| {
-            // FORWARD
-            parent
-                .spawn((
-                    ButtonBundle {
-                        style: BUTTON_STYLE,
-                        background_color: BUTTON_COLOR.into(),
+            // One row per action, with a button per binding slot
+            for (action, label) in REBINDABLE_ACTIONS {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(8.0),
+                            ..default()
+                        },
                         ..default()
-                    },
-                    ActionButton(Action::Forward),
-                    ColoredButton,
-                ))
-                .with_children(|parent| {
-                    parent.spawn(default_text("Forward ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
-                });
-            // BACKWARD
-            parent
-                .spawn((
-                    ButtonBundle {
-                        style: BUTTON_STYLE,
-                        background_color: BUTTON_COLOR.into(),
-                        ..default()
-                    },
-                    ActionButton(Action::Backward),
-                    ColoredButton,
-                ))
-                .with_children(|parent| {
-                    parent.spawn(default_text("Backward ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
-                });
-            // LEFT
-            parent
-                .spawn((
-                    ButtonBundle {
-                        style: BUTTON_STYLE,
-                        background_color: BUTTON_COLOR.into(),
-                        ..default()
-                    },
-                    ActionButton(Action::Left),
-                    ColoredButton,
-                ))
-                .with_children(|parent| {
-                    parent.spawn(default_text("Left ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
-                });
-            // Right
-            parent
-                .spawn((
-                    ButtonBundle {
-                        style: BUTTON_STYLE,
-                        background_color: BUTTON_COLOR.into(),
-                        ..default()
-                    },
-                    ActionButton(Action::Right),
-                    ColoredButton,
-                ))
-                .with_children(|parent| {
-                    parent.spawn(default_text("Right ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
-                });
-            // Jump
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(default_text(label, 32.0, asset_server).with_style(
+                            Style {
+                                width: Val::Px(LABEL_WIDTH),
+                                ..default()
+                            },
+                        ));
+                        for slot in 0..BINDING_SLOTS {
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: Style {
+                                            width: Val::Px(BINDING_BUTTON_WIDTH),
+                                            height: Val::Px(BUTTON_HEIGHT * 0.6),
+                                            ..BUTTON_STYLE
+                                        },
+                                        background_color: BUTTON_COLOR.into(),
+                                        ..default()
+                                    },
+                                    ActionButton { action, slot },
+                                    ColoredButton,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn((
+                                        default_text("", 24.0, asset_server),
+                                        KeyText,
+                                    ));
+                                });
+                        }
+                    });
+            }
+            parent.spawn(default_text(
+                "Click a binding, then press the new input. Esc cancels, Delete clears.",
+                20.0,
+                asset_server,
+            ));
+            // VIEW DISTANCE
             parent
                 .spawn((
                     ButtonBundle {
@@ -107,14 +100,13 @@ pub fn build_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
                         background_color: BUTTON_COLOR.into(),
                         ..default()
                     },
-                    ActionButton(Action::Jump),
+                    ViewDistanceButton,
                     ColoredButton,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(default_text("Jump ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
+                    parent.spawn((default_text("", 32.0, asset_server), ViewDistanceText));
                 });
-            // Crouch
+            // STORM ASSIST
             parent
                 .spawn((
                     ButtonBundle {
@@ -122,14 +114,13 @@ pub fn build_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
                         background_color: BUTTON_COLOR.into(),
                         ..default()
                     },
-                    ActionButton(Action::Crouch),
+                    StormAssistButton,
                     ColoredButton,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(default_text("Crouch ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
+                    parent.spawn((default_text("", 32.0, asset_server), StormAssistText));
                 });
-            // Interact
+            // NEW WORLD
             parent
                 .spawn((
                     ButtonBundle {
@@ -137,14 +128,13 @@ pub fn build_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
                         background_color: BUTTON_COLOR.into(),
                         ..default()
                     },
-                    ActionButton(Action::Interact),
+                    ControlsNewWorld,
                     ColoredButton,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(default_text("Interact ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
+                    parent.spawn(default_text("New world", 32.0, asset_server));
                 });
-            // Place beacon
+            // RESET
             parent
                 .spawn((
                     ButtonBundle {
@@ -152,12 +142,11 @@ pub fn build_menu(commands: &mut Commands, asset_server: &Res<AssetServer>) -> E
                         background_color: BUTTON_COLOR.into(),
                         ..default()
                     },
-                    ActionButton(Action::PlaceBeacon),
+                    ControlsReset,
                     ColoredButton,
                 ))
                 .with_children(|parent| {
-                    parent.spawn(default_text("Place beacon ", 32.0, asset_server));
-                    parent.spawn((default_text("", 32.0, asset_server), KeyText));
+                    parent.spawn(default_text("Reset to defaults", 32.0, asset_server));
                 });
             // BACK
             parent
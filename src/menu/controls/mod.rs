@@ -12,31 +12,96 @@ impl Plugin for ControlsMenuPlugin {
                 Update,
                 (
                     interaction::interact_back_button,
+                    interaction::interact_reset_button,
+                    interaction::interact_view_distance_button,
+                    interaction::interact_storm_assist_button,
+                    interaction::interact_new_world_button,
                     interaction::interact_action_button,
                     interaction::update_button_text,
-                ),
+                    interaction::update_view_distance_text,
+                    interaction::update_storm_assist_text,
+                )
+                    .chain()
+                    .after(MenuToggleSet)
+                    .after(MenuNavigationSet)
+                    .run_if(in_state(MenuState::Controls)),
             )
             .add_systems(OnEnter(MenuState::Controls), layout::spawn_menu)
-            .add_systems(OnExit(MenuState::Controls), layout::despawn_menu);
+            .add_systems(OnExit(MenuState::Controls), layout::despawn_menu)
+            .add_systems(OnExit(MenuState::Controls), |mut state: ResMut<ButtonState>| {
+                state.0 = None
+            });
     }
 }
 
 use crate::input::Action;
 
-use super::MenuState;
+use super::{navigation::MenuNavigationSet, MenuState, MenuToggleSet};
+
+/// Number of bindings shown per action (primary and secondary)
+pub const BINDING_SLOTS: usize = 2;
+
+/// Actions that can be rebound, with their label in the menu
+pub const REBINDABLE_ACTIONS: [(Action, &str); 15] = [
+    (Action::Forward, "Forward"),
+    (Action::Backward, "Backward"),
+    (Action::Left, "Left"),
+    (Action::Right, "Right"),
+    (Action::Move, "Move"),
+    (Action::View, "View (mouse)"),
+    (Action::ViewStick, "View (stick)"),
+    (Action::Jump, "Jump"),
+    (Action::Crouch, "Crouch"),
+    (Action::Interact, "Interact"),
+    (Action::PlaceBeacon, "Place beacon"),
+    (Action::Map, "Map"),
+    (Action::DropBattery, "Drop battery"),
+    (Action::BeaconColor, "Beacon color"),
+    (Action::LabelBeacon, "Label beacon"),
+];
 
 #[derive(Component)]
 pub struct ControlsMenu;
 
-#[derive(Component)]
-pub struct ActionButton(Action);
+/// A button showing one binding slot of an action
+#[derive(Component, Clone, Copy, PartialEq, Eq, Reflect)]
+pub struct ActionButton {
+    pub action: Action,
+    pub slot: usize,
+}
 
 #[derive(Component)]
 pub struct KeyText;
 
+/// The binding slot currently waiting for an input, if any
 #[derive(Resource, Reflect)]
 #[reflect(Resource)]
-pub struct ButtonState(pub Option<Action>);
+pub struct ButtonState(pub Option<ActionButton>);
 
 #[derive(Component)]
 pub struct ControlsBack;
+
+#[derive(Component)]
+pub struct ControlsReset;
+
+/// Cycles through the view distances
+#[derive(Component)]
+pub struct ViewDistanceButton;
+
+#[derive(Component)]
+pub struct ViewDistanceText;
+
+/// Toggles the storm assist
+#[derive(Component)]
+pub struct StormAssistButton;
+
+#[derive(Component)]
+pub struct StormAssistText;
+
+/// Opens the world creation menu
+#[derive(Component)]
+pub struct ControlsNewWorld;
+
+pub fn is_capturing(button_state: Res<ButtonState>) -> bool {
+    button_state.0.is_some()
+}
@@ -1,33 +1,63 @@
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 use controls::ControlsMenuPlugin;
+use leafwing_input_manager::common_conditions::action_just_pressed;
+use navigation::{MenuAction, MenuNavigationPlugin};
 use styling::MenuStylingPlugin;
+use world::WorldMenuPlugin;
 
 use crate::{input::cursor_is_grabbed, util::switch_to_state};
 
 mod controls;
+pub mod navigation;
 
 #[allow(unused)]
 pub mod styling;
+mod world;
 
 pub struct MenuPlugin;
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins((MenuStylingPlugin, ControlsMenuPlugin))
+        app.add_plugins((
+            MenuStylingPlugin,
+            MenuNavigationPlugin,
+            ControlsMenuPlugin,
+            WorldMenuPlugin,
+        ))
             .add_systems(
                 Update,
                 (
                     switch_to_state(MenuState::Controls).run_if(in_state(MenuState::None)),
-                    switch_to_state(MenuState::None).run_if(in_state(MenuState::Controls)),
+                    switch_to_state(MenuState::None).run_if(
+                        in_state(MenuState::Controls)
+                            .or_else(in_state(MenuState::World))
+                            .or_else(in_state(MenuState::Map)),
+                    ),
                 )
-                    .run_if(input_just_pressed(KeyCode::Escape).and_then(not(cursor_is_grabbed))),
+                    .run_if(
+                        action_just_pressed(MenuAction::Toggle)
+                            .and_then(not(cursor_is_grabbed))
+                            .and_then(not(controls::is_capturing)),
+                    )
+                    .in_set(MenuToggleSet),
             )
             .init_state::<MenuState>();
     }
 }
 
+/// Opening and closing menus with Escape or Start. Systems that also react to Escape (like
+/// cancelling a rebind) should run after this set, so the menu stays open on that frame.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct MenuToggleSet;
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Default, States)]
 pub enum MenuState {
     #[default]
     None,
     Controls,
+    /// Choosing a terrain preset to regenerate the world from
+    World,
+    /// The world map, see `crate::map`
+    Map,
+    /// Typing the label of a beacon, see `crate::beacon`
+    BeaconLabel,
 }
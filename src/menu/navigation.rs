@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{
+    action_state::ActionState,
+    input_map::InputMap,
+    plugin::{InputManagerPlugin, ToggleActions},
+    Actionlike,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{controls::is_capturing, styling::ColoredButton};
+
+pub struct MenuNavigationPlugin;
+impl Plugin for MenuNavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(InputManagerPlugin::<MenuAction>::default())
+            .init_resource::<ActionState<MenuAction>>()
+            .insert_resource(ToggleActions::<MenuAction>::ENABLED)
+            .insert_resource(menu_input_map())
+            .insert_resource(FocusedButton(None))
+            .add_event::<ButtonActivated>()
+            .add_systems(
+                Update,
+                (pointer_interactions, move_focus, confirm_focus)
+                    .chain()
+                    .in_set(MenuNavigationSet)
+                    .run_if(not(is_capturing)),
+            );
+    }
+}
+
+/// Focus and activation of buttons. Systems reading [`ButtonActivated`] should run after this set.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct MenuNavigationSet;
+
+/// Menu inputs, these are not rebindable and stay enabled while the cursor is free
+#[derive(Actionlike, Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect, Serialize, Deserialize)]
+pub enum MenuAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Confirm,
+    Back,
+    Toggle,
+}
+
+fn menu_input_map() -> InputMap<MenuAction> {
+    let mut input_map = InputMap::default();
+    input_map.insert(MenuAction::Up, KeyCode::ArrowUp);
+    input_map.insert(MenuAction::Up, GamepadButtonType::DPadUp);
+    input_map.insert(MenuAction::Down, KeyCode::ArrowDown);
+    input_map.insert(MenuAction::Down, GamepadButtonType::DPadDown);
+    input_map.insert(MenuAction::Left, KeyCode::ArrowLeft);
+    input_map.insert(MenuAction::Left, GamepadButtonType::DPadLeft);
+    input_map.insert(MenuAction::Right, KeyCode::ArrowRight);
+    input_map.insert(MenuAction::Right, GamepadButtonType::DPadRight);
+    input_map.insert(MenuAction::Confirm, KeyCode::Enter);
+    input_map.insert(MenuAction::Confirm, KeyCode::NumpadEnter);
+    input_map.insert(MenuAction::Confirm, GamepadButtonType::South);
+    input_map.insert(MenuAction::Back, GamepadButtonType::East);
+    input_map.insert(MenuAction::Toggle, KeyCode::Escape);
+    input_map.insert(MenuAction::Toggle, GamepadButtonType::Start);
+    input_map
+}
+
+/// The button that confirm activates, and that is highlighted
+#[derive(Resource)]
+pub struct FocusedButton(pub Option<Entity>);
+
+/// Sent when a button is clicked, or confirmed while focused
+#[derive(Event)]
+pub struct ButtonActivated(pub Entity);
+
+fn pointer_interactions(
+    q_button: Query<(Entity, &Interaction), (Changed<Interaction>, With<ColoredButton>)>,
+    mut focused: ResMut<FocusedButton>,
+    mut ev_activated: EventWriter<ButtonActivated>,
+) {
+    for (e, interaction) in &q_button {
+        match *interaction {
+            Interaction::Pressed => {
+                focused.0 = Some(e);
+                ev_activated.send(ButtonActivated(e));
+            }
+            Interaction::Hovered => focused.0 = Some(e),
+            Interaction::None => {}
+        }
+    }
+}
+
+fn move_focus(
+    actions: Res<ActionState<MenuAction>>,
+    mut focused: ResMut<FocusedButton>,
+    q_buttons: Query<(Entity, &GlobalTransform, &ViewVisibility), With<ColoredButton>>,
+) {
+    // UI coordinates grow downwards
+    let dir = if actions.just_pressed(&MenuAction::Up) {
+        Vec2::NEG_Y
+    } else if actions.just_pressed(&MenuAction::Down) {
+        Vec2::Y
+    } else if actions.just_pressed(&MenuAction::Left) {
+        Vec2::NEG_X
+    } else if actions.just_pressed(&MenuAction::Right) {
+        Vec2::X
+    } else {
+        return;
+    };
+
+    let visible = || {
+        q_buttons
+            .iter()
+            .filter(|(_, _, vis)| vis.get())
+            .map(|(e, tr, _)| (e, tr.translation().xy()))
+    };
+
+    let Some(current) = focused
+        .0
+        .and_then(|e| visible().find(|(other, _)| *other == e))
+    else {
+        focused.0 = first_button(visible());
+        return;
+    };
+
+    // Closest button in that direction, favoring the ones that are aligned with it
+    let next = visible()
+        .filter_map(|(e, pos)| {
+            let offset = pos - current.1;
+            let along = offset.dot(dir);
+            (along > 1.0).then_some((e, along + offset.perp_dot(dir).abs() * 2.0))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    if let Some((e, _)) = next {
+        focused.0 = Some(e);
+    }
+}
+
+fn confirm_focus(
+    actions: Res<ActionState<MenuAction>>,
+    mut focused: ResMut<FocusedButton>,
+    q_buttons: Query<(Entity, &GlobalTransform, &ViewVisibility), With<ColoredButton>>,
+    mut ev_activated: EventWriter<ButtonActivated>,
+) {
+    if !actions.just_pressed(&MenuAction::Confirm) {
+        return;
+    }
+    match focused.0.filter(|e| q_buttons.get(*e).is_ok_and(|(_, _, vis)| vis.get())) {
+        Some(e) => {
+            ev_activated.send(ButtonActivated(e));
+        }
+        None => {
+            focused.0 = first_button(
+                q_buttons
+                    .iter()
+                    .filter(|(_, _, vis)| vis.get())
+                    .map(|(e, tr, _)| (e, tr.translation().xy())),
+            );
+        }
+    }
+}
+
+/// The top-left-most button
+fn first_button(buttons: impl Iterator<Item = (Entity, Vec2)>) -> Option<Entity> {
+    buttons
+        .min_by(|(_, a), (_, b)| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)))
+        .map(|(e, _)| e)
+}
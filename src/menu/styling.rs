@@ -1,9 +1,14 @@
 use bevy::{color::palettes::css::*, prelude::*};
 
+use super::navigation::{FocusedButton, MenuNavigationSet};
+
 pub struct MenuStylingPlugin;
 impl Plugin for MenuStylingPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, highlight_button_interactions);
+        app.add_systems(
+            Update,
+            highlight_button_interactions.after(MenuNavigationSet),
+        );
     }
 }
 
@@ -12,6 +17,7 @@ pub const DEFAULT_BACKGROUND_COLOR: Srgba = Srgba::NONE;
 pub const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
 pub const HOVERED_BUTTON_COLOR: Color = Color::srgb(0.25, 0.25, 0.25);
 pub const PRESSED_BUTTON_COLOR: Color = Color::srgb(0.35, 0.75, 0.35);
+pub const FOCUS_OUTLINE_COLOR: Color = Color::srgb(0.91, 0.83, 0.49);
 
 pub const BUTTON_WIDTH: f32 = 400.0;
 pub const BUTTON_HEIGHT: f32 = 80.0;
@@ -26,24 +32,51 @@ pub const BUTTON_STYLE: Style = {
 };
 
 pub fn highlight_button_interactions(
+    mut cmds: Commands,
+    focused: Res<FocusedButton>,
+    mut prev_focused: Local<Option<Entity>>,
     mut q_buttons: Query<
-        (&Interaction, &mut BackgroundColor),
-        (Changed<Interaction>, With<ColoredButton>),
+        (
+            Entity,
+            Ref<Interaction>,
+            &mut BackgroundColor,
+            Option<&mut Outline>,
+        ),
+        With<ColoredButton>,
     >,
 ) {
-    for (interaction, mut background_color) in &mut q_buttons {
-        match *interaction {
-            Interaction::Pressed => {
+    for (e, interaction, mut background_color, outline) in &mut q_buttons {
+        let is_focused = focused.0 == Some(e);
+        let focus_changed = focused.is_changed() && (is_focused || *prev_focused == Some(e));
+        if !interaction.is_changed() && !focus_changed {
+            continue;
+        }
+
+        match (*interaction, is_focused) {
+            (Interaction::Pressed, _) => {
                 *background_color = PRESSED_BUTTON_COLOR.into();
             }
-            Interaction::Hovered => {
+            (Interaction::Hovered, _) | (_, true) => {
                 *background_color = HOVERED_BUTTON_COLOR.into();
             }
-            Interaction::None => {
+            (Interaction::None, false) => {
                 *background_color = BUTTON_COLOR.into();
             }
         }
+
+        let outline_color = match is_focused {
+            true => FOCUS_OUTLINE_COLOR,
+            false => Color::NONE,
+        };
+        match outline {
+            Some(mut outline) => outline.color = outline_color,
+            None => {
+                cmds.entity(e)
+                    .insert(Outline::new(Val::Px(3.0), Val::Px(2.0), outline_color));
+            }
+        }
     }
+    *prev_focused = focused.0;
 }
 
 #[derive(Component)]
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+use leafwing_input_manager::action_state::ActionState;
+
+use crate::terrain::{TerrainPreset, TerrainPresets};
+
+use super::{
+    navigation::{ButtonActivated, MenuAction, MenuNavigationSet},
+    styling::{default_text, ColoredButton, BUTTON_COLOR, BUTTON_STYLE, DEFAULT_BACKGROUND_COLOR},
+    MenuState, MenuToggleSet,
+};
+
+const SELECTED_TEXT_COLOR: Color = Color::srgb(0.91, 0.83, 0.49);
+
+/// Regenerates the world from one of the terrain presets
+pub struct WorldMenuPlugin;
+impl Plugin for WorldMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                interact_back_button,
+                interact_preset_button,
+                update_preset_text,
+            )
+                .chain()
+                .after(MenuToggleSet)
+                .after(MenuNavigationSet)
+                .run_if(in_state(MenuState::World)),
+        )
+        .add_systems(OnEnter(MenuState::World), spawn_menu)
+        .add_systems(OnExit(MenuState::World), despawn_menu);
+    }
+}
+
+#[derive(Component)]
+struct WorldMenu;
+
+/// Generates the world from the preset at that index of [`TerrainPresets`]
+#[derive(Component)]
+struct PresetButton(usize);
+
+#[derive(Component)]
+struct PresetText(usize);
+
+#[derive(Component)]
+struct WorldBack;
+
+fn spawn_menu(mut cmds: Commands, asset_server: Res<AssetServer>, presets: Res<TerrainPresets>) {
+    cmds.spawn((
+        Name::new("World Menu"),
+        NodeBundle {
+            style: Style {
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                row_gap: Val::Px(8.0),
+                ..default()
+            },
+            background_color: DEFAULT_BACKGROUND_COLOR.into(),
+            ..default()
+        },
+        WorldMenu,
+    ))
+    .with_children(|parent| {
+        parent.spawn(default_text("New world", 48.0, &asset_server));
+        // PRESETS
+        for i in 0..presets.handles.len() {
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: BUTTON_STYLE,
+                        background_color: BUTTON_COLOR.into(),
+                        ..default()
+                    },
+                    PresetButton(i),
+                    ColoredButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((default_text("", 32.0, &asset_server), PresetText(i)));
+                });
+        }
+        // BACK
+        parent
+            .spawn((
+                ButtonBundle {
+                    style: BUTTON_STYLE,
+                    background_color: BUTTON_COLOR.into(),
+                    ..default()
+                },
+                WorldBack,
+                ColoredButton,
+            ))
+            .with_children(|parent| {
+                parent.spawn(default_text("Back", 32.0, &asset_server));
+            });
+    });
+}
+
+fn despawn_menu(mut cmds: Commands, q_menu: Query<Entity, With<WorldMenu>>) {
+    for e in &q_menu {
+        cmds.entity(e).despawn_recursive();
+    }
+}
+
+fn interact_back_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<(), With<WorldBack>>,
+    menu_actions: Res<ActionState<MenuAction>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    let back = menu_actions.just_pressed(&MenuAction::Back);
+    if back || ev_activated.read().any(|ev| q_button.contains(ev.0)) {
+        menu_state.set(MenuState::Controls);
+    }
+}
+
+fn interact_preset_button(
+    mut ev_activated: EventReader<ButtonActivated>,
+    q_button: Query<&PresetButton>,
+    mut presets: ResMut<TerrainPresets>,
+) {
+    for ev in ev_activated.read() {
+        if let Ok(PresetButton(i)) = q_button.get(ev.0) {
+            presets.selected = *i;
+        }
+    }
+}
+
+/// Presets are named once they are loaded
+fn update_preset_text(
+    mut q_text: Query<(&mut Text, &PresetText)>,
+    presets: Res<TerrainPresets>,
+    assets: Res<Assets<TerrainPreset>>,
+) {
+    for (mut text, PresetText(i)) in &mut q_text {
+        let section = &mut text.sections[0];
+        section.value = match assets.get(&presets.handles[*i]) {
+            Some(preset) => preset.name.clone(),
+            None => "Loading...".to_string(),
+        };
+        section.style.color = match *i == presets.selected {
+            true => SELECTED_TEXT_COLOR,
+            false => Color::WHITE,
+        };
+    }
+}
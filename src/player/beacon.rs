@@ -1,8 +1,14 @@
 use avian3d::spatial_query::{SpatialQuery, SpatialQueryFilter};
 use bevy::prelude::*;
 
-use crate::{beacon::Beacon, camera::CameraRange};
+use crate::{
+    beacon::Beacon,
+    camera::CameraRange,
+    map::{LandmarkKind, MapLandmark},
+    terrain::OnTerrain,
+};
 
+/// Beacons the player can still plant, retracted ones are given back
 #[derive(Component, Reflect)]
 pub struct BeaconCount(pub usize);
 
@@ -13,6 +19,9 @@ pub fn place_beacon(
     spatial: SpatialQuery,
 ) {
     for (e, mut beacons) in &mut q_player {
+        if beacons.0 == 0 {
+            continue;
+        }
         let Ok((cam_tr, range)) = q_camera.get_single() else {
             continue;
         };
@@ -26,9 +35,11 @@ pub fn place_beacon(
             SpatialQueryFilter::from_excluded_entities([e]),
         ) {
             let p = origin + dir * hit.time_of_impact;
-            beacons.0 += 1;
+            beacons.0 -= 1;
             cmds.spawn((
                 Beacon,
+                OnTerrain,
+                MapLandmark(LandmarkKind::Beacon),
                 SpatialBundle::from_transform(Transform::from_translation(p)),
             ));
         }
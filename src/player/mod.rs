@@ -14,7 +14,8 @@ use crate::{
 
 mod beacon;
 mod spawn;
-pub use spawn::SpawnPlayer;
+pub use beacon::BeaconCount;
+pub use spawn::{SpawnPlayer, PLAYER_SPEED};
 
 pub struct PlayerPlugin;
 impl Plugin for PlayerPlugin {
@@ -11,10 +11,12 @@ use super::{beacon::BeaconCount, Inventory, Player};
 use crate::{
     camera::follow::Eyes,
     movement::{GroundSensorBundle, MovementInput, OnGround, Speed},
+    terrain::PhysicsAnchor,
 };
 
 pub const PLAYER_HEIGHT: f32 = 1.8;
 pub const PLAYER_RADIUS: f32 = 0.5;
+pub const PLAYER_SPEED: f32 = 10.0;
 pub const PLAYER_EYE_OFFSET: f32 = (PLAYER_HEIGHT * 0.92) / 2.0; // relative to center of body
 
 #[derive(Event)]
@@ -32,6 +34,7 @@ pub fn player_spawn(
         cmds.spawn((
             Name::new("Player"),
             Player,
+            PhysicsAnchor,
             Inventory {
                 batteries: vec![],
             },
@@ -53,7 +56,7 @@ pub fn player_spawn(
                     combine_rule: CoefficientCombine::Min,
                 },
             ),
-            (OnGround(false), MovementInput::default(), Speed(10.0)),
+            (OnGround(false), MovementInput::default(), Speed(PLAYER_SPEED)),
             BeaconCount(10),
         ))
         .with_children(|cmds| {
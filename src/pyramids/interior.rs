@@ -0,0 +1,464 @@
+use avian3d::prelude::{Collider, CollidingEntities, LinearVelocity, RigidBody, Sensor};
+use bevy::prelude::*;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    player::Player, shelter::ShelterSafeZone, terrain::TerrainParams, util::spatial_playback_remove,
+};
+
+use super::Pyramid;
+
+/// Rooms, corridors and traps generated inside pyramids the player gets close to
+pub struct PyramidInteriorPlugin;
+impl Plugin for PyramidInteriorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_assets)
+            .add_systems(Update, (stream_interiors, spring_traps));
+    }
+}
+
+// Rooms are laid out on a square grid, entered from the middle of the last row (+z)
+const GRID: u32 = 3;
+const ROOM_SIZE: f32 = 7.2;
+const ENTRANCE_ROOM: UVec2 = UVec2::new(GRID / 2, GRID - 1);
+// The battery of the pyramid stands in the middle room, a monolith can be in the back corner
+const BATTERY_ROOM: UVec2 = UVec2::new(GRID / 2, GRID / 2);
+const MONOLITH_ROOM: UVec2 = UVec2::new(0, 0);
+// Doors added on top of the ones needed to reach every room, making loops
+const EXTRA_DOORS: usize = 1;
+const TRAP_COUNT: usize = 2;
+
+// Interior dimensions, the ceiling stays under the slopes of the pyramid
+const WALL_HEIGHT: f32 = 6.0;
+const WALL_THICKNESS: f32 = 0.5;
+const DOOR_WIDTH: f32 = 2.4;
+const FLOOR_SIZE: f32 = 31.8;
+// The doorway of the model, from the last row of rooms to the outer face
+const DOORWAY_WIDTH: f32 = 5.4;
+const DOORWAY_HEIGHT: f32 = 5.3;
+const DOORWAY_END: f32 = 15.8;
+// Slope in front of the doorway, going down into the sand
+const RAMP_START: Vec3 = Vec3::new(0.0, 0.2, 15.6);
+const RAMP_END: Vec3 = Vec3::new(0.0, -4.0, 20.8);
+const RAMP_WIDTH: f32 = 12.4;
+
+const ROOM_LIGHT_COLOR: Color = Color::srgb(1.0, 0.7, 0.4);
+const WALL_COLOR: Color = Color::srgb(0.72, 0.6, 0.42);
+const TRAP_COLOR: Color = Color::srgb(0.5, 0.4, 0.28);
+const TRAP_SIZE: f32 = 2.5;
+// Sand bursting out of a trap throws the player back toward the entrance
+const TRAP_PUSH: f32 = 14.0;
+const TRAP_LIFT: f32 = 5.0;
+const TRAP_SOUND_VOLUME: f32 = 2.0;
+
+// Interiors are spawned when the player comes this close, and removed beyond the second distance
+const LOAD_DISTANCE: f32 = 90.0;
+const UNLOAD_DISTANCE: f32 = 130.0;
+
+/// Where a monolith stands inside a pyramid, relative to it
+pub fn monolith_spot() -> Vec3 {
+    room_center(MONOLITH_ROOM).extend(0.0).xzy()
+}
+
+/// Center of a room, relative to the pyramid
+fn room_center(room: UVec2) -> Vec2 {
+    (room.as_vec2() + 0.5 - GRID as f32 / 2.0) * ROOM_SIZE
+}
+
+/// Rooms are connected through doors, two neighbouring rooms without one are separated by a wall
+#[derive(Debug, PartialEq)]
+struct Layout {
+    doors: Vec<(UVec2, UVec2)>,
+    traps: Vec<UVec2>,
+}
+
+impl Layout {
+    fn has_door(&self, a: UVec2, b: UVec2) -> bool {
+        self.doors.contains(&(a, b)) || self.doors.contains(&(b, a))
+    }
+}
+
+fn neighbours(room: UVec2) -> impl Iterator<Item = UVec2> {
+    [IVec2::X, IVec2::NEG_X, IVec2::Y, IVec2::NEG_Y]
+        .into_iter()
+        .map(move |dir| room.as_ivec2() + dir)
+        .filter(|n| n.cmpge(IVec2::ZERO).all() && n.cmplt(IVec2::splat(GRID as i32)).all())
+        .map(|n| n.as_uvec2())
+}
+
+/// A maze from the entrance, with a few loops and traps in rooms that hold nothing
+fn generate_layout(rng: &mut impl Rng) -> Layout {
+    let mut doors = Vec::new();
+    let mut visited = vec![ENTRANCE_ROOM];
+    let mut stack = vec![ENTRANCE_ROOM];
+    while let Some(&room) = stack.last() {
+        let unvisited = neighbours(room)
+            .filter(|n| !visited.contains(n))
+            .collect::<Vec<_>>();
+        match unvisited.choose(rng) {
+            Some(&next) => {
+                doors.push((room, next));
+                visited.push(next);
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+
+    let mut walls = (0..GRID * GRID)
+        .map(|i| UVec2::new(i % GRID, i / GRID))
+        .flat_map(|room| neighbours(room).map(move |n| (room, n)))
+        .filter(|(a, b)| (a.x, a.y) < (b.x, b.y))
+        .filter(|(a, b)| !doors.contains(&(*a, *b)) && !doors.contains(&(*b, *a)))
+        .collect::<Vec<_>>();
+    walls.shuffle(rng);
+    doors.extend(walls.into_iter().take(EXTRA_DOORS));
+
+    let mut empty_rooms = (0..GRID * GRID)
+        .map(|i| UVec2::new(i % GRID, i / GRID))
+        .filter(|room| ![ENTRANCE_ROOM, BATTERY_ROOM, MONOLITH_ROOM].contains(room))
+        .collect::<Vec<_>>();
+    empty_rooms.shuffle(rng);
+    empty_rooms.truncate(TRAP_COUNT);
+
+    Layout {
+        doors,
+        traps: empty_rooms,
+    }
+}
+
+/// Part of a wall, centered on the floor, relative to the pyramid
+#[derive(Debug, PartialEq)]
+struct Wall {
+    center: Vec2,
+    size: Vec2,
+}
+
+/// Wall along an axis aligned line, with a door in the middle if needed
+fn wall_segments(from: Vec2, to: Vec2, door: bool, door_width: f32) -> Vec<Wall> {
+    let along = (to - from).normalize();
+    let thickness = Vec2::splat(WALL_THICKNESS) * (Vec2::ONE - along.abs());
+    let wall = |a: Vec2, b: Vec2| Wall {
+        center: (a + b) / 2.0,
+        size: (b - a).abs() + thickness,
+    };
+    match door {
+        true => {
+            let middle = (from + to) / 2.0;
+            vec![
+                wall(from, middle - along * door_width / 2.0),
+                wall(middle + along * door_width / 2.0, to),
+            ]
+        }
+        false => vec![wall(from, to)],
+    }
+}
+
+fn layout_walls(layout: &Layout) -> Vec<Wall> {
+    let half = GRID as f32 * ROOM_SIZE / 2.0;
+    let corner = |x: u32, y: u32| Vec2::new(x as f32, y as f32) * ROOM_SIZE - half;
+    let mut walls = Vec::new();
+    for y in 0..GRID {
+        for x in 0..GRID {
+            let room = UVec2::new(x, y);
+            // Wall on the +x side of the room
+            let door = x + 1 < GRID && layout.has_door(room, room + UVec2::X);
+            walls.extend(wall_segments(
+                corner(x + 1, y),
+                corner(x + 1, y + 1),
+                door,
+                DOOR_WIDTH,
+            ));
+            // Wall on the +z side, leading outside from the entrance room
+            let (door, width) = match y + 1 < GRID {
+                true => (layout.has_door(room, room + UVec2::Y), DOOR_WIDTH),
+                false => (room == ENTRANCE_ROOM, DOORWAY_WIDTH),
+            };
+            walls.extend(wall_segments(
+                corner(x, y + 1),
+                corner(x + 1, y + 1),
+                door,
+                width,
+            ));
+        }
+        // Outer walls on the -x side, and the -z side of the grid
+        walls.extend(wall_segments(corner(0, y), corner(0, y + 1), false, 0.0));
+        walls.extend(wall_segments(corner(y, 0), corner(y + 1, 0), false, 0.0));
+    }
+    // The doorway leading to the outer face of the pyramid
+    for side in [-1.0, 1.0] {
+        let x = side * (DOORWAY_WIDTH + WALL_THICKNESS) / 2.0;
+        walls.extend(wall_segments(
+            Vec2::new(x, half),
+            Vec2::new(x, DOORWAY_END),
+            false,
+            0.0,
+        ));
+    }
+    walls
+}
+
+/// Seeded from the world and where the pyramid stands, so it is the same every time it loads
+fn layout_rng(seed: u32, pos: Vec3) -> StdRng {
+    let pos = pos.xz().round().as_ivec2();
+    StdRng::seed_from_u64(
+        (seed as u64) ^ (((pos.x as u32 as u64) << 32) | pos.y as u32 as u64).rotate_left(17),
+    )
+}
+
+#[derive(Resource)]
+struct InteriorAssets {
+    cube: Handle<Mesh>,
+    wall_material: Handle<StandardMaterial>,
+    trap_material: Handle<StandardMaterial>,
+    trap_sound: Handle<AudioSource>,
+}
+
+/// Root of the generated interior, child of the pyramid
+#[derive(Component)]
+struct LoadedInterior(Entity);
+
+/// Throws the player back when stepped on
+#[derive(Component)]
+struct Trap;
+
+fn setup_assets(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    cmds.insert_resource(InteriorAssets {
+        cube: meshes.add(Cuboid::new(1.0, 1.0, 1.0)),
+        wall_material: materials.add(StandardMaterial {
+            base_color: WALL_COLOR,
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        trap_material: materials.add(StandardMaterial {
+            base_color: TRAP_COLOR,
+            perceptual_roughness: 0.9,
+            ..default()
+        }),
+        trap_sound: asset_server.load("audio/sfx/wind_1.ogg"),
+    });
+}
+
+fn stream_interiors(
+    mut cmds: Commands,
+    assets: Res<InteriorAssets>,
+    tp: Res<TerrainParams>,
+    q_player: Query<&GlobalTransform, With<Player>>,
+    q_pyramids: Query<(Entity, &GlobalTransform, Option<&LoadedInterior>), With<Pyramid>>,
+) {
+    let Ok(player_tr) = q_player.get_single() else {
+        return;
+    };
+    let player_pos = player_tr.translation();
+    for (pyramid_e, pyramid_tr, loaded) in &q_pyramids {
+        let dist = pyramid_tr.translation().distance(player_pos);
+        match loaded {
+            None if dist < LOAD_DISTANCE => {
+                let layout = generate_layout(&mut layout_rng(tp.seed(), pyramid_tr.translation()));
+                let interior = spawn_interior(&mut cmds, &assets, &layout);
+                cmds.entity(interior).set_parent(pyramid_e);
+                cmds.entity(pyramid_e).insert(LoadedInterior(interior));
+            }
+            Some(interior) if dist > UNLOAD_DISTANCE => {
+                cmds.entity(interior.0).despawn_recursive();
+                cmds.entity(pyramid_e).remove::<LoadedInterior>();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn spawn_interior(cmds: &mut Commands, assets: &InteriorAssets, layout: &Layout) -> Entity {
+    let half = GRID as f32 * ROOM_SIZE / 2.0;
+    let block = |center: Vec3, size: Vec3| {
+        (
+            PbrBundle {
+                mesh: assets.cube.clone(),
+                material: assets.wall_material.clone(),
+                transform: Transform::from_translation(center).with_scale(size),
+                ..default()
+            },
+            RigidBody::Static,
+            Collider::cuboid(1.0, 1.0, 1.0),
+        )
+    };
+
+    cmds.spawn((Name::new("Pyramid interior"), SpatialBundle::default()))
+        .with_children(|parent| {
+            // The floor of the model has no collider, the sand is a few meters below it
+            parent.spawn((
+                RigidBody::Static,
+                Collider::cuboid(FLOOR_SIZE, 0.4, FLOOR_SIZE),
+                TransformBundle::from_transform(Transform::from_xyz(0.0, -0.2, 0.0)),
+            ));
+            let ramp = RAMP_END - RAMP_START;
+            parent.spawn((
+                RigidBody::Static,
+                Collider::cuboid(RAMP_WIDTH, 0.4, ramp.length()),
+                TransformBundle::from_transform(
+                    Transform::from_translation((RAMP_START + RAMP_END) / 2.0 + Vec3::NEG_Y * 0.2)
+                        .looking_to(ramp, Vec3::Y),
+                ),
+            ));
+            parent.spawn(block(
+                Vec3::Y * (WALL_HEIGHT + WALL_THICKNESS / 2.0),
+                Vec3::new(half * 2.0, WALL_THICKNESS, half * 2.0),
+            ));
+            parent.spawn(block(
+                Vec3::new(
+                    0.0,
+                    DOORWAY_HEIGHT + WALL_THICKNESS / 2.0,
+                    (half + DOORWAY_END) / 2.0,
+                ),
+                Vec3::new(DOORWAY_WIDTH, WALL_THICKNESS, DOORWAY_END - half),
+            ));
+            for wall in layout_walls(layout) {
+                parent.spawn(block(
+                    wall.center.extend(WALL_HEIGHT / 2.0).xzy(),
+                    wall.size.extend(WALL_HEIGHT).xzy(),
+                ));
+            }
+
+            for y in 0..GRID {
+                for x in 0..GRID {
+                    let center = room_center(UVec2::new(x, y));
+                    parent.spawn(PointLightBundle {
+                        point_light: PointLight {
+                            color: ROOM_LIGHT_COLOR,
+                            intensity: 40_000.0,
+                            range: ROOM_SIZE * 1.5,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            center.extend(WALL_HEIGHT - 1.0).xzy(),
+                        ),
+                        ..default()
+                    });
+                }
+            }
+            for room in &layout.traps {
+                parent.spawn((
+                    Trap,
+                    Sensor,
+                    Collider::cuboid(1.0, 1.0, 1.0),
+                    PbrBundle {
+                        mesh: assets.cube.clone(),
+                        material: assets.trap_material.clone(),
+                        transform: Transform::from_translation(
+                            room_center(*room).extend(0.0).xzy(),
+                        )
+                        .with_scale(Vec3::new(TRAP_SIZE, 0.04, TRAP_SIZE)),
+                        ..default()
+                    },
+                ));
+            }
+
+            // Sheltered from the storm anywhere inside
+            parent.spawn((
+                ShelterSafeZone,
+                Sensor,
+                Collider::cuboid(half * 2.0, WALL_HEIGHT, half * 2.0),
+                TransformBundle::from_transform(Transform::from_xyz(0.0, WALL_HEIGHT / 2.0, 0.0)),
+            ));
+        })
+        .id()
+}
+
+/// Traps go off every time the player steps on them
+fn spring_traps(
+    mut cmds: Commands,
+    assets: Res<InteriorAssets>,
+    mut q_player: Query<(&CollidingEntities, &mut LinearVelocity), With<Player>>,
+    q_traps: Query<&GlobalTransform, With<Trap>>,
+    mut stepped_on: Local<Vec<Entity>>,
+) {
+    let Ok((colliding, mut velocity)) = q_player.get_single_mut() else {
+        return;
+    };
+    let traps = colliding
+        .iter()
+        .copied()
+        .filter(|e| q_traps.contains(*e))
+        .collect::<Vec<_>>();
+    for &trap_e in traps.iter().filter(|e| !stepped_on.contains(e)) {
+        let trap_tr = q_traps.get(trap_e).unwrap();
+        // The entrance is on the +z side of the pyramid
+        let back = trap_tr.to_scale_rotation_translation().1 * Vec3::Z;
+        velocity.0 = back * TRAP_PUSH + Vec3::Y * TRAP_LIFT;
+        cmds.entity(trap_e).insert(AudioBundle {
+            source: assets.trap_sound.clone(),
+            settings: spatial_playback_remove(TRAP_SOUND_VOLUME, 0.4),
+        });
+    }
+    *stepped_on = traps;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        generate_layout, layout_walls, neighbours, BATTERY_ROOM, ENTRANCE_ROOM, GRID,
+        MONOLITH_ROOM, TRAP_COUNT,
+    };
+
+    #[test]
+    fn every_room_reachable() {
+        for seed in 0..50 {
+            let layout = generate_layout(&mut StdRng::seed_from_u64(seed));
+            let mut reached = vec![ENTRANCE_ROOM];
+            let mut stack = vec![ENTRANCE_ROOM];
+            while let Some(room) = stack.pop() {
+                for n in neighbours(room) {
+                    if layout.has_door(room, n) && !reached.contains(&n) {
+                        reached.push(n);
+                        stack.push(n);
+                    }
+                }
+            }
+            assert_eq!(reached.len(), (GRID * GRID) as usize);
+
+            assert_eq!(layout.traps.len(), TRAP_COUNT);
+            for room in [ENTRANCE_ROOM, BATTERY_ROOM, MONOLITH_ROOM] {
+                assert!(!layout.traps.contains(&room));
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let a = generate_layout(&mut StdRng::seed_from_u64(7));
+        let b = generate_layout(&mut StdRng::seed_from_u64(7));
+        assert_eq!(a, b);
+        assert_eq!(layout_walls(&a), layout_walls(&b));
+    }
+
+    #[test]
+    fn doors_are_open() {
+        let layout = generate_layout(&mut StdRng::seed_from_u64(3));
+        let walls = layout_walls(&layout);
+        let blocked = |p: Vec2| {
+            walls.iter().any(|wall| {
+                let d = (p - wall.center).abs();
+                d.x <= wall.size.x / 2.0 && d.y <= wall.size.y / 2.0
+            })
+        };
+        for (a, b) in &layout.doors {
+            let middle = (super::room_center(*a) + super::room_center(*b)) / 2.0;
+            assert!(!blocked(middle));
+        }
+        // The way in, and the middle of the rooms
+        assert!(!blocked(Vec2::new(0.0, 14.0)));
+        for i in 0..GRID * GRID {
+            assert!(!blocked(super::room_center(UVec2::new(i % GRID, i / GRID))));
+        }
+    }
+}
@@ -1,57 +1,108 @@
+mod interior;
+
 use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use std::f32::consts::TAU;
 
 use crate::{
     game::{GameTime, CYCLE_LENGTH},
-    terrain::TerrainParams,
-    util::poisson_disc_sampling,
+    map::{LandmarkKind, MapLandmark},
+    materials::beam::{beam_bundle, BeamMaterial, BeamMesh},
+    terrain::{PlacementSet, TerrainEdits, TerrainParams, TerrainSampler},
+    util::{despawn_all, poisson_disc_sampling_with},
 };
 
 pub struct PyramidPlugin;
 impl Plugin for PyramidPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, setup).add_systems(
-            Update,
-            pyramid_light_beam.run_if(resource_exists::<GameTime>),
-        );
+        app.add_plugins(interior::PyramidInteriorPlugin)
+            .add_systems(Update, despawn_all::<Pyramid>.in_set(PlacementSet::Clear))
+            .add_systems(Update, setup.in_set(PlacementSet::Scenes))
+            .add_systems(
+                Update,
+                pyramid_light_beam.run_if(resource_exists::<GameTime>),
+            );
     }
 }
 
 #[derive(Component)]
 pub struct Pyramid;
 
-fn setup(mut cmds: Commands, asset_server: Res<AssetServer>, terrain_params: Res<TerrainParams>) {
+pub use interior::monolith_spot;
+
+// Radius of the ground levelled under a pyramid
+const PYRAMID_PAD_RADIUS: f32 = 40.0;
+// The beam comes out of the top of the pyramid
+const BEAM_BASE: f32 = 20.3;
+const BEAM_COLOR: LinearRgba = LinearRgba::new(1.0, 5.0, 5.0, 0.5);
+const BEAM_RADIUS: f32 = 1.2;
+const BEAM_HEIGHT: f32 = 2000.0;
+
+/// Shared by the beams of all pyramids, which fade together
+#[derive(Resource)]
+struct PyramidBeam(Handle<BeamMaterial>);
+
+fn setup(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    tp: Res<TerrainParams>,
+    terrain: Res<TerrainSampler>,
+    mut edits: ResMut<TerrainEdits>,
+    beam_mesh: Res<BeamMesh>,
+    mut beam_materials: ResMut<Assets<BeamMaterial>>,
+) {
+    let beam = beam_materials.add(BeamMaterial { color: BEAM_COLOR });
+    cmds.insert_resource(PyramidBeam(beam.clone()));
+    let mut rng = StdRng::seed_from_u64(tp.seed() as u64 + 3);
     let region = 5000.0;
-    for p in poisson_disc_sampling(700.0, region, 5, vec![Vec2::splat(region / 2.0)]) {
+    let spots =
+        poisson_disc_sampling_with(&mut rng, 700.0, region, 5, vec![Vec2::splat(region / 2.0)]);
+    for p in spots {
         let p = p - region / 2.0;
-        let height = terrain_params.get_height(p) + 3.0;
+        if !terrain.biome(p).allows_pyramids() {
+            continue;
+        }
+        let ground = terrain.height(p);
+        edits.flatten(p, PYRAMID_PAD_RADIUS, ground);
+        let height = ground + 3.0;
         cmds.spawn((
             Name::new("Pyramid"),
             Pyramid,
+            MapLandmark(LandmarkKind::Pyramid),
             SceneBundle {
                 scene: asset_server.load("levels/Pyramid.glb#Scene0"),
                 transform: Transform::from_translation(p.extend(height).xzy())
-                    .with_rotation(Quat::from_rotation_y(rand::random::<f32>() * TAU)),
+                    .with_rotation(Quat::from_rotation_y(rng.r#gen::<f32>() * TAU)),
                 ..default()
             },
-        ));
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Name::new("Pyramid beam"),
+                beam_bundle(
+                    &beam_mesh,
+                    beam.clone(),
+                    Vec3::Y * BEAM_BASE,
+                    BEAM_RADIUS,
+                    BEAM_HEIGHT,
+                ),
+            ));
+        });
     }
 }
 
-pub fn pyramid_light_beam(
-    mut gizmos: Gizmos,
-    q_pyramids: Query<&Transform, With<Pyramid>>,
+/// Beams fade out during the second half of the cycle
+fn pyramid_light_beam(
+    beam: Res<PyramidBeam>,
+    mut beam_materials: ResMut<Assets<BeamMaterial>>,
     time: Res<GameTime>,
 ) {
-    for tr in &q_pyramids {
-        let fade_t = ((time.time / CYCLE_LENGTH - 0.5) * 2.0).clamp(0.0, 1.0);
-        let alpha = 1.0 - (fade_t * 3.0).clamp(0.0, 1.0);
-        if alpha > 0.0 {
-            gizmos.ray(
-                tr.translation,
-                Vec3::Y * 10000.0,
-                Srgba::rgb(1.0 * alpha, 5.0 * alpha, 5.0 * alpha).with_alpha(alpha * 0.5),
-            );
+    let fade_t = ((time.time / CYCLE_LENGTH - 0.5) * 2.0).clamp(0.0, 1.0);
+    let alpha = 1.0 - (fade_t * 3.0).clamp(0.0, 1.0);
+    let color = BEAM_COLOR.with_alpha(BEAM_COLOR.alpha * alpha);
+    if let Some(material) = beam_materials.get_mut(&beam.0) {
+        if material.color != color {
+            material.color = color;
         }
     }
 }
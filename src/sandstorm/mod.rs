@@ -1,24 +1,33 @@
 pub mod post_process;
 mod sound;
 
+use std::{collections::VecDeque, f32::consts::TAU};
+
 use bevy::{pbr::NotShadowCaster, prelude::*};
 use bevy_kira_audio::{AudioInstance, AudioTween};
 use post_process::PostProcessSettings;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use sound::SandstormAudioInstances;
 
-use crate::camera::CameraShake;
+use crate::{
+    camera::{CameraShake, MainCamera},
+    terrain::{PlacementSet, TerrainEdits, TerrainParams},
+};
 
 pub struct SandstormPlugin;
 impl Plugin for SandstormPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<SandstormIntensity>()
             .insert_resource(SandstormIntensity(0.0))
+            .init_resource::<SandDrifts>()
             .add_plugins((post_process::PostProcessPlugin, sound::SandstormSoundPlugin))
             .add_systems(Startup, setup)
             .add_systems(
                 Update,
                 (update_visuals, update_audio).run_if(resource_changed::<SandstormIntensity>),
-            );
+            )
+            .add_systems(Update, deposit_drifts)
+            .add_systems(Update, calm_storm.in_set(PlacementSet::Clear));
     }
 }
 
@@ -52,6 +61,13 @@ fn setup(
 #[reflect(Resource)]
 pub struct SandstormIntensity(pub f32);
 
+impl SandstormIntensity {
+    /// Distance the fog lets the player see through the storm, in meters
+    pub fn visibility(&self) -> f32 {
+        4000.0 * (1.0 - self.0.powf(0.1))
+    }
+}
+
 fn update_visuals(
     mut settings: Query<
         (&mut PostProcessSettings, &mut FogSettings, &mut CameraShake),
@@ -63,7 +79,7 @@ fn update_visuals(
 ) {
     for (mut setting, mut fog, mut shake) in &mut settings {
         setting.strength = intensity.0 * 0.95;
-        fog.falloff = FogFalloff::from_visibility(4000.0 * (1.0 - intensity.0.powf(0.1)));
+        fog.falloff = FogFalloff::from_visibility(intensity.visibility());
         let mat_handle = q_skybox_cover.single();
         let mat = materials.get_mut(mat_handle).unwrap();
         mat.base_color.set_alpha(intensity.0.powf(0.15));
@@ -90,3 +106,70 @@ fn update_audio(
         .get_mut(audio_instances.strong.id())
         .map(|s| s.set_volume(strong as f64, AudioTween::default()));
 }
+
+// Storms stronger than this pile up sand drifts around the camera
+const DRIFT_MIN_INTENSITY: f32 = 0.5;
+// Seconds between drifts at full intensity
+const DRIFT_INTERVAL: f32 = 4.0;
+// Past this many drifts, the oldest ones are blown away
+const MAX_DRIFTS: usize = 48;
+
+#[derive(Clone, Copy)]
+struct Drift {
+    center: Vec2,
+    radius: f32,
+    amount: f32,
+}
+
+/// Sand piled up by the storms, oldest first
+#[derive(Resource, Default, Clone)]
+pub struct SandDrifts {
+    drifts: VecDeque<Drift>,
+    // Every drift is seeded from the world and how many came before it
+    deposited: u64,
+}
+
+/// A new world starts without storm or drifts, they were removed with the other edits
+fn calm_storm(mut cmds: Commands) {
+    cmds.insert_resource(SandstormIntensity(0.0));
+    cmds.insert_resource(SandDrifts::default());
+}
+
+fn deposit_drifts(
+    time: Res<Time>,
+    intensity: Res<SandstormIntensity>,
+    q_camera: Query<&GlobalTransform, With<MainCamera>>,
+    tp: Res<TerrainParams>,
+    mut drifts: ResMut<SandDrifts>,
+    mut edits: ResMut<TerrainEdits>,
+    mut elapsed: Local<f32>,
+) {
+    if intensity.0 < DRIFT_MIN_INTENSITY {
+        return;
+    }
+    *elapsed += time.delta_seconds() * intensity.0;
+    if *elapsed < DRIFT_INTERVAL {
+        return;
+    }
+    *elapsed = 0.0;
+
+    let Ok(camera) = q_camera.get_single() else {
+        return;
+    };
+    let mut rng = StdRng::seed_from_u64(((tp.seed() as u64) << 32) ^ drifts.deposited);
+    drifts.deposited += 1;
+    // Out of the way of the player, but close enough to be seen through the storm
+    let dir = Vec2::from_angle(rng.gen_range(0.0..TAU));
+    let drift = Drift {
+        center: camera.translation().xz() + dir * rng.gen_range(40.0..200.0),
+        radius: rng.gen_range(20.0..40.0),
+        amount: 1.5 * intensity.0,
+    };
+    edits.raise(drift.center, drift.radius, drift.amount);
+    drifts.drifts.push_back(drift);
+
+    if drifts.drifts.len() > MAX_DRIFTS {
+        let old = drifts.drifts.pop_front().unwrap();
+        edits.lower(old.center, old.radius, old.amount);
+    }
+}
@@ -1,7 +1,7 @@
 mod fs;
 
 use bevy::prelude::*;
-use leafwing_input_manager::{axislike::DualAxis, input_map::InputMap};
+use leafwing_input_manager::{axislike::DualAxis, input_map::InputMap, user_input::UserInput};
 use serde::{Deserialize, Serialize};
 
 use crate::input::Action;
@@ -9,20 +9,32 @@ use crate::input::Action;
 pub struct SettingsPlugin;
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Startup, load_settings)
+        app.register_type::<ViewDistance>()
+            .register_type::<StormAssist>()
+            .add_systems(Startup, load_settings)
             .add_systems(Update, save_settings);
     }
 }
 
 fn load_settings(mut cmds: Commands) {
     let settings = fs::load_settings();
-    cmds.insert_resource(settings.input_map.clone());
+    let mut input_map = settings.input_map.clone();
+    migrate_stick_view(&mut input_map);
+    cmds.insert_resource(input_map);
+    cmds.insert_resource(settings.view_distance);
+    cmds.insert_resource(settings.storm_assist);
 }
 
-fn save_settings(input_map: Res<InputMap<Action>>) {
-    if input_map.is_changed() {
+fn save_settings(
+    input_map: Res<InputMap<Action>>,
+    view_distance: Res<ViewDistance>,
+    storm_assist: Res<StormAssist>,
+) {
+    if input_map.is_changed() || view_distance.is_changed() || storm_assist.is_changed() {
         fs::save_settings(&Settings {
             input_map: input_map.clone(),
+            view_distance: *view_distance,
+            storm_assist: *storm_assist,
         });
     }
 }
@@ -30,25 +42,138 @@ fn save_settings(input_map: Res<InputMap<Action>>) {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Settings {
     input_map: InputMap<Action>,
+    // Older settings files don't have it
+    #[serde(default)]
+    view_distance: ViewDistance,
+    #[serde(default)]
+    storm_assist: StormAssist,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        let mut input_map = InputMap::default();
-        input_map.insert(Action::Forward, KeyCode::KeyW);
-        input_map.insert(Action::Backward, KeyCode::KeyS);
-        input_map.insert(Action::Left, KeyCode::KeyA);
-        input_map.insert(Action::Right, KeyCode::KeyD);
-        input_map.insert(Action::Jump, KeyCode::Space);
-        input_map.insert(Action::Crouch, KeyCode::ControlLeft);
-        input_map.insert(Action::Interact, KeyCode::KeyE);
-        input_map.insert(Action::PlaceBeacon, KeyCode::KeyR);
-        input_map.insert(
-            Action::View,
-            DualAxis::mouse_motion()
-                .with_sensitivity(0.001, 0.001)
-                .inverted(),
-        );
-        Self { input_map }
+        Self {
+            input_map: default_input_map(),
+            view_distance: ViewDistance::default(),
+            storm_assist: StormAssist::default(),
+        }
+    }
+}
+
+/// How far terrain is generated and drawn around the camera
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub enum ViewDistance {
+    Near,
+    #[default]
+    Medium,
+    Far,
+    /// Coarser terrain up to the horizon
+    Horizon,
+}
+
+impl ViewDistance {
+    pub fn next(self) -> Self {
+        match self {
+            ViewDistance::Near => ViewDistance::Medium,
+            ViewDistance::Medium => ViewDistance::Far,
+            ViewDistance::Far => ViewDistance::Horizon,
+            ViewDistance::Horizon => ViewDistance::Near,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ViewDistance::Near => "Near",
+            ViewDistance::Medium => "Medium",
+            ViewDistance::Far => "Far",
+            ViewDistance::Horizon => "Horizon",
+        }
+    }
+
+    /// Far plane of the camera, in meters
+    pub fn draw_distance(self) -> f32 {
+        match self {
+            ViewDistance::Near | ViewDistance::Medium => 1000.0,
+            ViewDistance::Far => 2500.0,
+            ViewDistance::Horizon => 10000.0,
+        }
+    }
+}
+
+/// Points the player to the nearest shelter when the storm begins
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Reflect)]
+#[reflect(Resource)]
+pub struct StormAssist(pub bool);
+
+impl StormAssist {
+    pub fn label(self) -> &'static str {
+        match self.0 {
+            true => "On",
+            false => "Off",
+        }
+    }
+}
+
+pub fn default_input_map() -> InputMap<Action> {
+    let mut input_map = InputMap::default();
+    input_map.insert(Action::Forward, KeyCode::KeyW);
+    input_map.insert(Action::Backward, KeyCode::KeyS);
+    input_map.insert(Action::Left, KeyCode::KeyA);
+    input_map.insert(Action::Right, KeyCode::KeyD);
+    input_map.insert(Action::Jump, KeyCode::Space);
+    input_map.insert(Action::Crouch, KeyCode::ControlLeft);
+    input_map.insert(Action::Interact, KeyCode::KeyE);
+    input_map.insert(Action::PlaceBeacon, KeyCode::KeyR);
+    input_map.insert(Action::Map, KeyCode::Tab);
+    input_map.insert(Action::Map, GamepadButtonType::Select);
+    input_map.insert(Action::DropBattery, KeyCode::KeyG);
+    input_map.insert(Action::BeaconColor, KeyCode::KeyC);
+    input_map.insert(Action::LabelBeacon, KeyCode::KeyT);
+    input_map.insert(Action::Move, DualAxis::left_stick());
+    input_map.insert(Action::View, mouse_view_axis());
+    input_map.insert(Action::ViewStick, stick_view_axis(DualAxis::right_stick()));
+    input_map
+}
+
+/// Mouse look, scaled from pixels to radians
+pub fn mouse_view_axis() -> DualAxis {
+    DualAxis::mouse_motion()
+        .with_sensitivity(0.001, 0.001)
+        .inverted()
+}
+
+/// Gamepad look, scaled from stick deflection to radians per second
+pub fn stick_view_axis(stick: DualAxis) -> DualAxis {
+    stick.with_sensitivity(2.4, 2.4).inverted()
+}
+
+// Older settings files bind sticks to mouse look, scaled to radians per frame
+const LEGACY_STICK_SENSITIVITY: f32 = 0.04;
+
+/// Moves the sticks bound to mouse look by older settings files to stick look
+fn migrate_stick_view(map: &mut InputMap<Action>) {
+    let legacy = |stick: &DualAxis| -> UserInput {
+        stick
+            .clone()
+            .with_sensitivity(LEGACY_STICK_SENSITIVITY, LEGACY_STICK_SENSITIVITY)
+            .inverted()
+            .into()
+    };
+    let view = map.get(&Action::View).cloned().unwrap_or_default();
+    let sticks = [DualAxis::left_stick(), DualAxis::right_stick()]
+        .into_iter()
+        .filter(|stick| view.contains(&legacy(stick)))
+        .collect::<Vec<_>>();
+    if sticks.is_empty() {
+        return;
+    }
+    map.clear_action(&Action::View);
+    for input in view {
+        if !sticks.iter().any(|stick| legacy(stick) == input) {
+            map.insert(Action::View, input);
+        }
+    }
+    for stick in sticks {
+        map.insert(Action::ViewStick, stick_view_axis(stick));
     }
 }
@@ -2,34 +2,68 @@ use std::f32::consts::TAU;
 
 use avian3d::prelude::CollidingEntities;
 use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 
-use crate::{player::Player, terrain::TerrainParams, util::poisson_disc_sampling};
+use crate::{
+    map::{LandmarkKind, MapLandmark},
+    player::Player,
+    terrain::{PlacementSet, TerrainEdits, TerrainParams, TerrainSampler},
+    util::{despawn_all, poisson_disc_sampling_with},
+};
 
 pub struct ShelterPlugin;
 impl Plugin for ShelterPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<ShelterSafeZone>()
             .insert_resource(PlayerIsSafe(false))
-            .add_systems(Startup, setup)
+            .add_systems(Update, despawn_all::<Shelter>.in_set(PlacementSet::Clear))
+            .add_systems(Update, setup.in_set(PlacementSet::Scenes))
             .add_systems(Update, (check_safe_zones, safe_text));
     }
 }
 
+#[derive(Component)]
+pub struct Shelter;
+
 #[derive(Component, Reflect)]
 #[reflect(Component)]
 pub struct ShelterSafeZone;
 
-fn setup(mut cmds: Commands, asset_server: Res<AssetServer>, terrain_params: Res<TerrainParams>) {
+// Radius of the ground levelled under a shelter
+const SHELTER_PAD_RADIUS: f32 = 20.0;
+
+fn setup(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    tp: Res<TerrainParams>,
+    terrain: Res<TerrainSampler>,
+    mut edits: ResMut<TerrainEdits>,
+) {
+    let mut rng = StdRng::seed_from_u64(tp.seed() as u64 + 2);
     let region = 4000.0;
-    for p in poisson_disc_sampling(700.0, region, 30000, vec![Vec2::splat(region / 2.0)]) {
+    let spots = poisson_disc_sampling_with(
+        &mut rng,
+        700.0,
+        region,
+        30000,
+        vec![Vec2::splat(region / 2.0)],
+    );
+    for p in spots {
         let p = p - region / 2.0;
-        let height = terrain_params.get_height(p) + 3.0;
+        if !terrain.biome(p).allows_shelters() {
+            continue;
+        }
+        let ground = terrain.height(p);
+        edits.flatten(p, SHELTER_PAD_RADIUS, ground);
+        let height = ground + 3.0;
         cmds.spawn((
             Name::new("Shelter"),
+            Shelter,
+            MapLandmark(LandmarkKind::Shelter),
             SceneBundle {
                 scene: asset_server.load("levels/Shelter.glb#Scene0"),
                 transform: Transform::from_translation(p.extend(height).xzy())
-                    .with_rotation(Quat::from_rotation_y(rand::random::<f32>() * TAU)),
+                    .with_rotation(Quat::from_rotation_y(rng.r#gen::<f32>() * TAU)),
                 ..default()
             },
         ));
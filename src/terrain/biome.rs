@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Regions of the desert, each with its own ground and color
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect)]
+pub enum Biome {
+    SaltPan,
+    DuneSea,
+    RockyFlats,
+    Canyons,
+}
+
+impl Biome {
+    /// In the order of their bands in the biome noise, see [`weights`]
+    pub const ALL: [Biome; 4] = [
+        Biome::SaltPan,
+        Biome::DuneSea,
+        Biome::RockyFlats,
+        Biome::Canyons,
+    ];
+
+    /// Canyons are too cramped to shelter in
+    pub fn allows_shelters(self) -> bool {
+        self != Biome::Canyons
+    }
+
+    /// Pyramids stand on solid ground, away from the salt and the cliffs
+    pub fn allows_pyramids(self) -> bool {
+        matches!(self, Biome::DuneSea | Biome::RockyFlats)
+    }
+}
+
+/// How the ground of a biome is shaped from the terrain noise
+#[derive(Reflect, Clone, Copy, PartialEq, Debug, Deserialize)]
+pub struct BiomeParams {
+    /// Multiplies the frequency of the terrain noise
+    pub frequency: f32,
+    pub amplitude: f32,
+    pub power: f32,
+    pub skew: f32,
+    /// Height the ground is shaped around
+    pub base: f32,
+    /// Folds the noise so that valleys are carved along its zero crossings
+    pub ridged: bool,
+    /// Color of the ground, replacing the sand color by its alpha
+    pub tint: LinearRgba,
+}
+
+impl BiomeParams {
+    /// Height for the noise value `n`, and its derivative
+    pub fn shape(&self, n: f32) -> (f32, f32) {
+        // Folded noise is 1 on the zero crossings, which become the valley floors
+        let (v, dv_dn, sign) = match self.ridged {
+            true if n.abs() < 1.0 => (1.0 - n.abs(), -n.signum(), -1.0),
+            true => (0.0, 0.0, -1.0),
+            false => (n, 1.0, 1.0),
+        };
+        // Negative values can't be raised to fractional powers, the ground flattens out below
+        let skewed = v + self.skew;
+        let h = (skewed.max(0.0).powf(self.power) - self.skew) * self.amplitude;
+        let dh_dv = match skewed > 0.0 {
+            true => self.amplitude * self.power * skewed.powf(self.power - 1.0),
+            false => 0.0,
+        };
+        (self.base + sign * h, sign * dh_dv * dv_dn)
+    }
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Weight of each biome of [`Biome::ALL`] for the biome noise value `s`, and their derivatives.
+/// `bounds` separate the bands of the biomes, neighbouring biomes blend over a band of width
+/// `blend` around them.
+pub fn weights(s: f32, bounds: [f32; 3], blend: f32) -> ([f32; 4], [f32; 4]) {
+    // How far `s` is past each bound, from 0 to 1
+    let past = bounds.map(|bound| {
+        let t = ((s - bound) / blend + 0.5).clamp(0.0, 1.0);
+        (smoothstep(t), 6.0 * t * (1.0 - t) / blend)
+    });
+    let mut w = [0.0; 4];
+    let mut dw = [0.0; 4];
+    for i in 0..w.len() {
+        let below = if i == 0 { (1.0, 0.0) } else { past[i - 1] };
+        let above = past.get(i).copied().unwrap_or((0.0, 0.0));
+        w[i] = below.0 - above.0;
+        dw[i] = below.1 - above.1;
+    }
+    (w, dw)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{weights, Biome, BiomeParams};
+
+    const BOUNDS: [f32; 3] = [-0.4, 0.2, 0.4];
+
+    #[test]
+    fn weights_blend() {
+        let blend = 0.1;
+        for i in -100..=100 {
+            let s = i as f32 / 100.0;
+            let (w, dw) = weights(s, BOUNDS, blend);
+            assert!((w.iter().sum::<f32>() - 1.0).abs() < 1e-5, "{s}");
+            assert!(w.iter().all(|w| *w >= 0.0));
+
+            let step = 1e-4;
+            let (below, _) = weights(s - step, BOUNDS, blend);
+            let (above, _) = weights(s + step, BOUNDS, blend);
+            for b in 0..w.len() {
+                let slope = (above[b] - below[b]) / (2.0 * step);
+                assert!(
+                    (dw[b] - slope).abs() < 0.05,
+                    "{s} {b}: {} vs {slope}",
+                    dw[b]
+                );
+            }
+        }
+
+        // Away from the borders, a single biome
+        let (w, _) = weights(0.0, BOUNDS, blend);
+        assert_eq!(w[Biome::DuneSea as usize], 1.0);
+        let (w, _) = weights(BOUNDS[2], BOUNDS, blend);
+        assert_eq!((w[2], w[3]), (0.5, 0.5));
+    }
+
+    #[test]
+    fn shape_below_skew() {
+        let params = BiomeParams {
+            frequency: 1.0,
+            amplitude: 10.0,
+            power: 2.5,
+            skew: 0.2,
+            base: 0.0,
+            ridged: false,
+            tint: LinearRgba::NONE,
+        };
+        for n in [-1.0, -0.5, -0.2, 0.0, 0.5] {
+            let (h, dh) = params.shape(n);
+            assert!(h.is_finite() && dh.is_finite(), "{n}: {h} {dh}");
+        }
+        assert_eq!(params.shape(-0.5), (-2.0, 0.0));
+    }
+}
@@ -0,0 +1,130 @@
+use avian3d::{math::Scalar, prelude::*};
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{edits::TerrainEdited, sampler::update_sampler, TerrainParams, TerrainSampler};
+
+/// Heightfield colliders are kept around every body that could touch the ground, independently
+/// of the visual LOD tree which only follows the camera.
+pub struct TerrainCollidersPlugin;
+impl Plugin for TerrainCollidersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PhysicsAnchor>()
+            .init_resource::<TerrainColliders>()
+            .add_systems(Update, stream_colliders.after(update_sampler));
+    }
+}
+
+/// Distance around an anchor in which terrain colliders are kept alive
+const ANCHOR_RADIUS: f32 = 64.0;
+
+/// Keeps terrain colliders alive around this entity, whatever its rigid body.
+/// Dynamic rigid bodies, asleep or not, are always anchors. Static and kinematic ones are only
+/// anchors with this component.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PhysicsAnchor;
+
+#[derive(Component)]
+pub struct ColliderChunk(pub IVec2);
+
+#[derive(Default, Resource)]
+pub struct TerrainColliders {
+    chunks: HashMap<IVec2, Entity>,
+}
+
+fn stream_colliders(
+    mut cmds: Commands,
+    mut colliders: ResMut<TerrainColliders>,
+    q_anchors: Query<
+        (&GlobalTransform, Option<&RigidBody>, Has<PhysicsAnchor>),
+        Or<(With<PhysicsAnchor>, With<RigidBody>)>,
+    >,
+    tp: Res<TerrainParams>,
+    sampler: Res<TerrainSampler>,
+    mut ev_edited: EventReader<TerrainEdited>,
+) {
+    if tp.is_changed() {
+        for (_, e) in colliders.chunks.drain() {
+            cmds.entity(e).despawn_recursive();
+        }
+    }
+
+    // Edited chunks are respawned below if they are still needed
+    for TerrainEdited(rect) in ev_edited.read() {
+        colliders.chunks.retain(|coord, e| {
+            let chunk_rect =
+                Rect::from_center_size(coord.as_vec2() * tp.size, Vec2::splat(tp.size));
+            let edited = !chunk_rect.intersect(*rect).is_empty();
+            if edited {
+                cmds.entity(*e).despawn_recursive();
+            }
+            !edited
+        });
+    }
+
+    let mut needed = HashSet::new();
+    for (tr, rb, anchor) in &q_anchors {
+        // Static and kinematic bodies don't fall, they only need ground when asked to
+        if !anchor && rb.is_some_and(|rb| !rb.is_dynamic()) {
+            continue;
+        }
+        let pos = tr.translation().xz();
+        // Chunk `c` spans `c * size ± size / 2`
+        let min = ((pos - ANCHOR_RADIUS) / tp.size).round().as_ivec2();
+        let max = ((pos + ANCHOR_RADIUS) / tp.size).round().as_ivec2();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                needed.insert(IVec2::new(x, y));
+            }
+        }
+    }
+
+    colliders.chunks.retain(|coord, e| {
+        let keep = needed.contains(coord);
+        if !keep {
+            cmds.entity(*e).despawn_recursive();
+        }
+        keep
+    });
+
+    for coord in needed {
+        if colliders.chunks.contains_key(&coord) {
+            continue;
+        }
+        let e = cmds
+            .spawn((
+                Name::new("Terrain Collider"),
+                ColliderChunk(coord),
+                CollisionMargin(0.1),
+                RigidBody::Static,
+                Collider::heightfield(
+                    create_heightfield(&tp, &sampler, coord),
+                    Vec3::new(tp.size, 1.0, tp.size),
+                ),
+                TransformBundle::from_transform(Transform::from_translation(
+                    coord.as_vec2().extend(0.0).xzy() * tp.size,
+                )),
+            ))
+            .id();
+        colliders.chunks.insert(coord, e);
+    }
+}
+
+/// Full resolution heights of a chunk, laid out as columns along x
+fn create_heightfield(
+    tp: &TerrainParams,
+    sampler: &TerrainSampler,
+    coord: IVec2,
+) -> Vec<Vec<Scalar>> {
+    let n = tp.nb_vertices + 1;
+    let spacing = tp.size / tp.nb_vertices as f32;
+    let origin = (coord.as_vec2() - 0.5) * tp.size;
+    let grid = sampler.sample_grid(origin, spacing, UVec2::splat(n as u32));
+
+    (0..n)
+        .map(|ix| (0..n).map(|iy| grid[iy * n + ix]).collect())
+        .collect()
+}
@@ -0,0 +1,311 @@
+use std::sync::Arc;
+
+use bevy::{prelude::*, utils::HashMap};
+
+/// Spacing of the points that can be edited, in meters
+pub const EDIT_CELL_SIZE: f32 = 8.0;
+// Points are stored by square tiles, so that untouched areas cost nothing
+const TILE_CELLS: i32 = 64;
+
+/// Flattened areas blend back into the terrain over this fraction of their radius
+const FLATTEN_BLEND: f32 = 0.5;
+
+/// Ground levelled at a fixed height, whatever is under it
+#[derive(Clone, Copy, PartialEq)]
+struct Pad {
+    center: Vec2,
+    radius: f32,
+    height: f32,
+}
+
+impl Pad {
+    fn blend(&self) -> f32 {
+        self.radius * FLATTEN_BLEND
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::from_center_half_size(self.center, Vec2::splat(self.radius + self.blend()))
+    }
+
+    /// How much the pad replaces the ground at `pos`, and the derivative of that
+    fn weight(&self, pos: Vec2) -> (f32, Vec2) {
+        let offset = pos - self.center;
+        let t = (offset.length() - self.radius) / self.blend();
+        if t >= 1.0 {
+            return (0.0, Vec2::ZERO);
+        }
+        if t <= 0.0 {
+            return (1.0, Vec2::ZERO);
+        }
+        let dw_dt = -6.0 * t * (1.0 - t);
+        (
+            1.0 - smoothstep(t),
+            offset.normalize() * dw_dt / self.blend(),
+        )
+    }
+}
+
+/// Height deltas added on top of the procedural terrain, interpolated between edit points,
+/// and pads that level the result
+#[derive(Default, Clone)]
+pub struct EditLayer {
+    // Tiles are shared between copies of the layer, an edit only copies the tiles it touches
+    tiles: HashMap<IVec2, Arc<Vec<f32>>>,
+    pads: Vec<Pad>,
+}
+
+impl EditLayer {
+    fn index(point: IVec2) -> usize {
+        let local = point.rem_euclid(IVec2::splat(TILE_CELLS));
+        (local.y * TILE_CELLS + local.x) as usize
+    }
+
+    fn get(&self, point: IVec2) -> f32 {
+        let tile = point.div_euclid(IVec2::splat(TILE_CELLS));
+        self.tiles
+            .get(&tile)
+            .map_or(0.0, |deltas| deltas[Self::index(point)])
+    }
+
+    fn get_mut(&mut self, point: IVec2) -> &mut f32 {
+        let tile = point.div_euclid(IVec2::splat(TILE_CELLS));
+        let deltas = self
+            .tiles
+            .entry(tile)
+            .or_insert_with(|| Arc::new(vec![0.0; (TILE_CELLS * TILE_CELLS) as usize]));
+        &mut Arc::make_mut(deltas)[Self::index(point)]
+    }
+
+    /// Areas that differ between the two layers
+    fn diff(&self, other: &EditLayer) -> Vec<Rect> {
+        let tile_size = TILE_CELLS as f32 * EDIT_CELL_SIZE;
+        let tiles = self
+            .tiles
+            .iter()
+            .filter(|(coord, deltas)| {
+                !other
+                    .tiles
+                    .get(*coord)
+                    .is_some_and(|other| Arc::ptr_eq(deltas, other))
+            })
+            .chain(
+                other
+                    .tiles
+                    .iter()
+                    .filter(|(coord, _)| !self.tiles.contains_key(*coord)),
+            )
+            .map(|(coord, _)| {
+                // Interpolation reaches one cell further
+                Rect::from_corners(
+                    coord.as_vec2() * tile_size - EDIT_CELL_SIZE,
+                    (coord.as_vec2() + 1.0) * tile_size + EDIT_CELL_SIZE,
+                )
+            });
+        let pads = self
+            .pads
+            .iter()
+            .filter(|pad| !other.pads.contains(pad))
+            .chain(other.pads.iter().filter(|pad| !self.pads.contains(pad)))
+            .map(Pad::bounds);
+        tiles.chain(pads).collect()
+    }
+
+    /// Fractional position in the cell and deltas at its corners
+    fn cell(&self, pos: Vec2) -> (Vec2, [f32; 4]) {
+        let grid = pos / EDIT_CELL_SIZE;
+        let min = grid.floor().as_ivec2();
+        let corners = [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE].map(|c| self.get(min + c));
+        (grid - grid.floor(), corners)
+    }
+
+    fn delta(&self, pos: Vec2) -> f32 {
+        if self.tiles.is_empty() {
+            return 0.0;
+        }
+        let (t, [h00, h10, h01, h11]) = self.cell(pos);
+        h00.lerp(h10, t.x).lerp(h01.lerp(h11, t.x), t.y)
+    }
+
+    /// Derivative of the delta along x and z
+    fn delta_gradient(&self, pos: Vec2) -> Vec2 {
+        if self.tiles.is_empty() {
+            return Vec2::ZERO;
+        }
+        let (t, [h00, h10, h01, h11]) = self.cell(pos);
+        Vec2::new(
+            (h10 - h00).lerp(h11 - h01, t.y),
+            (h01 - h00).lerp(h11 - h10, t.x),
+        ) / EDIT_CELL_SIZE
+    }
+
+    /// Edited height, from the procedural `base` height at `pos`
+    pub fn height(&self, pos: Vec2, base: f32) -> f32 {
+        self.pads.iter().fold(base + self.delta(pos), |height, pad| {
+            height.lerp(pad.height, pad.weight(pos).0)
+        })
+    }
+
+    /// Edited derivative of the height, from the procedural `base` height and its gradient
+    pub fn gradient(&self, pos: Vec2, base: f32, base_gradient: Vec2) -> Vec2 {
+        let start = (base + self.delta(pos), base_gradient + self.delta_gradient(pos));
+        let (_, gradient) = self.pads.iter().fold(start, |(height, gradient), pad| {
+            let (w, dw) = pad.weight(pos);
+            (
+                height.lerp(pad.height, w),
+                gradient * (1.0 - w) + (pad.height - height) * dw,
+            )
+        });
+        gradient
+    }
+}
+
+/// Sent for every area of the terrain that has been edited, once the sampler includes the edit
+#[derive(Event)]
+pub struct TerrainEdited(pub Rect);
+
+/// Runtime modifications of the terrain. Meshes and colliders of the edited areas are rebuilt.
+#[derive(Resource, Default)]
+pub struct TerrainEdits {
+    // Shared with the samplers, edits copy the tiles they change if a sampler still uses them
+    layer: Arc<EditLayer>,
+    dirty: Vec<Rect>,
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl TerrainEdits {
+    pub fn layer(&self) -> Arc<EditLayer> {
+        self.layer.clone()
+    }
+
+    /// Raises the ground by `amount` at the center, falling off smoothly to nothing at `radius`
+    pub fn raise(&mut self, center: Vec2, radius: f32, amount: f32) {
+        self.modify(center, radius, |_, weight, delta| delta + amount * weight);
+    }
+
+    pub fn lower(&mut self, center: Vec2, radius: f32, amount: f32) {
+        self.raise(center, radius, -amount);
+    }
+
+    /// Levels the ground to `height` within `radius`, then blends back into the terrain.
+    /// It stays level when the ground around it changes.
+    pub fn flatten(&mut self, center: Vec2, radius: f32, height: f32) {
+        let pad = Pad {
+            center,
+            radius,
+            height,
+        };
+        Arc::make_mut(&mut self.layer).pads.push(pad);
+        self.dirty.push(pad.bounds());
+    }
+
+    /// Goes back to a layer from [`TerrainEdits::layer`], rebuilding what changed since then
+    pub fn restore(&mut self, layer: Arc<EditLayer>) {
+        self.dirty.extend(self.layer.diff(&layer));
+        self.layer = layer;
+    }
+
+    /// Calls `f` with the position, falloff weight and current delta of every edit point in the
+    /// circle, and stores the new delta it returns
+    fn modify(&mut self, center: Vec2, radius: f32, f: impl Fn(Vec2, f32, f32) -> f32) {
+        let layer = Arc::make_mut(&mut self.layer);
+        let min = ((center - radius) / EDIT_CELL_SIZE).floor().as_ivec2();
+        let max = ((center + radius) / EDIT_CELL_SIZE).ceil().as_ivec2();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let point = IVec2::new(x, y);
+                let pos = point.as_vec2() * EDIT_CELL_SIZE;
+                let dist = pos.distance(center);
+                if dist > radius {
+                    continue;
+                }
+                let delta = layer.get_mut(point);
+                *delta = f(pos, smoothstep(1.0 - dist / radius), *delta);
+            }
+        }
+        // Interpolation reaches one cell further
+        self.dirty.push(Rect::from_center_half_size(
+            center,
+            Vec2::splat(radius + EDIT_CELL_SIZE),
+        ));
+    }
+
+    pub(super) fn take_dirty(&mut self) -> Vec<Rect> {
+        std::mem::take(&mut self.dirty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bevy::prelude::*;
+
+    use super::{TerrainEdits, EDIT_CELL_SIZE};
+    use crate::terrain::{TerrainParams, TerrainSampler};
+
+    #[test]
+    fn raise_and_lower() {
+        let mut edits = TerrainEdits::default();
+        let center = Vec2::new(-120.0, 48.0);
+        edits.raise(center, 40.0, 5.0);
+        let height = |pos| edits.layer.height(pos, 0.0);
+        assert!((height(center) - 5.0).abs() < 1e-4);
+        assert_eq!(height(center + Vec2::X * 60.0), 0.0);
+        assert!(height(center + Vec2::X * 20.0) > 0.0);
+        // The mound slopes down away from the center
+        let gradient = edits.layer.gradient(center + Vec2::X * 20.0, 0.0, Vec2::ZERO);
+        assert!(gradient.x < 0.0);
+
+        edits.lower(center, 40.0, 5.0);
+        assert!(edits.layer.height(center, 0.0).abs() < 1e-4);
+        assert_eq!(edits.take_dirty().len(), 2);
+        assert!(edits.take_dirty().is_empty());
+    }
+
+    #[test]
+    fn restore() {
+        let mut edits = TerrainEdits::default();
+        edits.raise(Vec2::ZERO, 30.0, 2.0);
+        let saved = edits.layer();
+
+        // Only the edited tile is copied, the others stay shared
+        let far = Vec2::splat(2000.0);
+        edits.raise(far, 30.0, 2.0);
+        assert!(Arc::ptr_eq(
+            &saved.tiles[&IVec2::ZERO],
+            &edits.layer.tiles[&IVec2::ZERO]
+        ));
+
+        edits.take_dirty();
+        edits.restore(saved);
+        let dirty = edits.take_dirty();
+        assert_eq!(dirty.len(), 1);
+        assert!(dirty[0].contains(far));
+        assert_eq!(edits.layer.height(far, 0.0), 0.0);
+        assert!(edits.layer.height(Vec2::ZERO, 0.0) > 0.0);
+    }
+
+    #[test]
+    fn flatten() {
+        let tp = TerrainParams::with_seed(1337);
+        let mut edits = TerrainEdits::default();
+        let center = Vec2::new(300.0, -700.0);
+        let mut sampler = TerrainSampler::new(&tp);
+        let height = sampler.height(center) + 2.0;
+        edits.flatten(center, 24.0, height);
+        // Raising the ground under the pad doesn't change it
+        edits.raise(center, 20.0, 3.0);
+        sampler.set_edits(edits.layer());
+
+        for offset in [Vec2::ZERO, Vec2::new(10.0, -5.0), Vec2::new(-12.0, 18.0)] {
+            let pos = center + offset;
+            assert!((sampler.height(pos) - height).abs() < 1e-3);
+            assert!(sampler.gradient(pos).length() < 1e-3);
+        }
+        let outside = center + Vec2::X * (24.0 * 1.5 + EDIT_CELL_SIZE);
+        assert_eq!(sampler.height(outside), sampler.base_height(outside));
+    }
+}
@@ -2,7 +2,10 @@ use std::convert::identity;
 
 use bevy::prelude::*;
 
-use super::{ring::Ring, ChunkReady, ChunkVisibility};
+use super::{
+    lod_mode_is, ring::DynRing, Chunk, ChunkReady, ChunkVisibility, LodCenter, LodMode,
+    NEIGHBOUR_OFFSETS,
+};
 
 pub struct Lod2dPlugin;
 
@@ -10,63 +13,173 @@ impl Plugin for Lod2dPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Lod2dTree::default())
             .insert_resource(InvalidSlots(vec![]))
-            .add_systems(Startup, setup)
-            .add_systems(PostUpdate, (update_lod, spawn_chunks, swap_chunks).chain());
+            .init_resource::<Lod2dConfig>()
+            .add_systems(
+                PostUpdate,
+                (
+                    apply_config,
+                    (update_lod, spawn_chunks, swap_chunks).run_if(lod_mode_is(LodMode::Grid)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+pub const DEFAULT_LOD_GRID_EXTENT: u32 = 2;
+pub const DEFAULT_LOD_LEVELS_PER_CHUNK_EXTENT: u32 = 2;
+
+/// Size of the LOD grid, can be changed at runtime.
+/// Changing it rebuilds every chunk.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Lod2dConfig {
+    /// Number of chunks on each side of the center, the outermost chunks have this LOD
+    pub grid_extent: u32,
+    /// Number of previous LODs kept on each side while the new one is being built
+    pub levels_per_chunk_extent: u32,
+}
+
+impl Default for Lod2dConfig {
+    fn default() -> Self {
+        Self {
+            grid_extent: DEFAULT_LOD_GRID_EXTENT,
+            levels_per_chunk_extent: DEFAULT_LOD_LEVELS_PER_CHUNK_EXTENT,
+        }
+    }
+}
+
+impl Lod2dConfig {
+    pub fn grid_len(&self) -> usize {
+        self.grid_extent as usize * 2 + 1
+    }
+
+    pub fn levels_per_chunk(&self) -> usize {
+        self.levels_per_chunk_extent as usize * 2 + 1
+    }
+
+    /// Offset from the center of the grid of the slot at row `r` and column `c`
+    fn offset(&self, r: usize, c: usize) -> IVec2 {
+        IVec2::new(c as i32, r as i32) - self.grid_extent as i32
     }
 }
 
 #[derive(Default, Resource)]
 pub struct Lod2dTree {
-    pub map: Ring<Ring<Slot, LOD_GRID_LEN>, LOD_GRID_LEN>,
+    pub map: DynRing<DynRing<Slot>>,
+    pub config: Lod2dConfig,
     pub pos: Vec2,
     pub prev_pos: Vec2,
 }
 
-pub const LOD_GRID_EXTENT: u32 = 2;
-pub const LOD_GRID_LEN: usize = (LOD_GRID_EXTENT as usize) * 2 + 1;
-
 impl Lod2dTree {
-    fn shift_up(&mut self) -> [Slot; LOD_GRID_LEN] {
-        self.map.shift_right().to_array()
+    fn new(config: Lod2dConfig, pos: Vec2) -> Self {
+        let mut tree = Self {
+            map: DynRing::default(),
+            config,
+            pos,
+            prev_pos: pos,
+        };
+        tree.map = (0..config.grid_len())
+            .map(|_| tree.new_row())
+            .collect::<Vec<_>>()
+            .into();
+        tree
+    }
+
+    fn new_row(&self) -> DynRing<Slot> {
+        (0..self.config.grid_len())
+            .map(|_| Slot::new(&self.config))
+            .collect::<Vec<_>>()
+            .into()
     }
 
-    fn shift_down(&mut self) -> [Slot; LOD_GRID_LEN] {
-        self.map.shift_left().to_array()
+    // Shifting leaves empty rings behind, they are replaced by slots of the right size
+
+    fn shift_up(&mut self) -> Vec<Slot> {
+        let removed = self.map.shift_right();
+        self.map[0] = self.new_row();
+        removed.into_vec()
     }
 
-    fn shift_right(&mut self) -> [Slot; LOD_GRID_LEN] {
+    fn shift_down(&mut self) -> Vec<Slot> {
+        let removed = self.map.shift_left();
+        let last = self.config.grid_len() - 1;
+        self.map[last] = self.new_row();
+        removed.into_vec()
+    }
+
+    fn shift_right(&mut self) -> Vec<Slot> {
+        let config = self.config;
         self.map
             .iter_mut()
-            .map(|d| d.shift_right())
-            .collect::<Vec<Slot>>()
-            .try_into()
-            .unwrap()
+            .map(|d| {
+                let removed = d.shift_right();
+                d[0] = Slot::new(&config);
+                removed
+            })
+            .collect()
     }
 
-    fn shift_left(&mut self) -> [Slot; LOD_GRID_LEN] {
+    fn shift_left(&mut self) -> Vec<Slot> {
+        let config = self.config;
+        let last = config.grid_len() - 1;
         self.map
             .iter_mut()
-            .map(|d| d.shift_left())
-            .collect::<Vec<Slot>>()
-            .try_into()
-            .unwrap()
+            .map(|d| {
+                let removed = d.shift_left();
+                d[last] = Slot::new(&config);
+                removed
+            })
+            .collect()
+    }
+}
+
+/// The chunk entities of a grid cell, the current LOD is in the middle
+#[derive(Debug, Default, Clone)]
+pub struct Slot(pub DynRing<Option<Entity>>);
+
+impl Slot {
+    fn new(config: &Lod2dConfig) -> Self {
+        Self(DynRing::new(config.levels_per_chunk()))
+    }
+
+    fn current(&self, config: &Lod2dConfig) -> Option<Entity> {
+        self.0[config.levels_per_chunk_extent as usize]
     }
 }
 
-pub const LOD_LEVELS_PER_CHUNK_EXTENT: u32 = 2;
-pub const LOD_LEVELS_PER_CHUNK: usize = (LOD_LEVELS_PER_CHUNK_EXTENT as usize) * 2 + 1;
+/// Rebuilds the grid when the config changes, and fills it the first time.
+/// The grid is emptied while another [`LodMode`] is used.
+fn apply_config(
+    mut cmds: Commands,
+    config: Res<Lod2dConfig>,
+    mode: Res<LodMode>,
+    center: Res<LodCenter>,
+    mut lod: ResMut<Lod2dTree>,
+    mut invalid_slots: ResMut<InvalidSlots>,
+) {
+    if !config.is_changed() && !mode.is_changed() {
+        return;
+    }
 
-#[derive(Debug, Default, Clone, Copy)]
-pub struct Slot(pub Ring<Option<Entity>, LOD_LEVELS_PER_CHUNK>);
+    for e in lod
+        .map
+        .iter()
+        .flat_map(|row| row.iter())
+        .flat_map(|s| s.0.iter())
+        .filter_map(|e| *e)
+    {
+        cmds.entity(e).despawn_recursive();
+    }
+    invalid_slots.0.clear();
+    if *mode != LodMode::Grid {
+        *lod = Lod2dTree::default();
+        return;
+    }
+    *lod = Lod2dTree::new(*config, center.0);
 
-fn setup(mut invalid_slots: ResMut<InvalidSlots>) {
-    for r in 0..LOD_GRID_LEN {
-        for c in 0..LOD_GRID_LEN {
-            let offset = IVec2::new(
-                c as i32 - LOD_GRID_EXTENT as i32,
-                r as i32 - LOD_GRID_EXTENT as i32,
-            );
-            invalid_slots.0.push(offset);
+    for r in 0..config.grid_len() {
+        for c in 0..config.grid_len() {
+            invalid_slots.0.push(config.offset(r, c));
         }
     }
 }
@@ -77,15 +190,18 @@ pub struct InvalidSlots(Vec<IVec2>);
 pub fn update_lod(
     mut cmds: Commands,
     mut lod: ResMut<Lod2dTree>,
-    // mut q_chunks: Query<&mut ChunkVisibility>,
+    center: Res<LodCenter>,
+    mut q_chunks: Query<&mut Chunk>,
     mut invalid_slots: ResMut<InvalidSlots>,
 ) {
+    lod.pos = center.0;
+    let config = lod.config;
     let prev_anchor = lod.prev_pos.round().as_ivec2();
     let new_anchor = lod.pos.round().as_ivec2();
 
     let offset = new_anchor - prev_anchor;
 
-    let mut despawn_slots = |slots: [Slot; LOD_GRID_LEN]| {
+    let mut despawn_slots = |slots: Vec<Slot>| {
         for e in slots.into_iter().flat_map(|s| s.0).filter_map(identity) {
             cmds.entity(e).despawn_recursive();
         }
@@ -105,12 +221,9 @@ pub fn update_lod(
     }
 
     if offset != IVec2::ZERO {
-        for r in 0..LOD_GRID_LEN {
-            for c in 0..LOD_GRID_LEN {
-                let offset = IVec2::new(
-                    c as i32 - LOD_GRID_EXTENT as i32,
-                    r as i32 - LOD_GRID_EXTENT as i32,
-                );
+        for r in 0..config.grid_len() {
+            for c in 0..config.grid_len() {
+                let offset = config.offset(r, c);
 
                 let prev_dist = (offset - (prev_anchor - new_anchor)).abs().max_element() as usize;
                 let new_dist = offset.abs().max_element() as usize;
@@ -133,6 +246,17 @@ pub fn update_lod(
                 if lod_offset != 0 {
                     invalid_slots.0.push(offset);
                 }
+
+                // The chunk keeps its LOD but its neighbours may have changed theirs
+                if let Some(mut chunk) = lod.map[r][c]
+                    .current(&config)
+                    .and_then(|e| q_chunks.get_mut(e).ok())
+                {
+                    let neighbour_lods = neighbour_lods(&config, offset);
+                    if chunk.neighbour_lods != neighbour_lods {
+                        chunk.neighbour_lods = neighbour_lods;
+                    }
+                }
             }
         }
     }
@@ -140,28 +264,37 @@ pub fn update_lod(
     lod.prev_pos = lod.pos;
 }
 
-#[derive(Component)]
-pub struct Chunk {
-    pub coord: IVec2,
-    pub lod: u32,
+fn offset_lod(offset: IVec2) -> u32 {
+    offset.abs().max_element() as u32
 }
 
-pub fn spawn_chunks(mut cmds: Commands, mut lod: ResMut<Lod2dTree>) {
-    for r in 0..LOD_GRID_LEN as i32 {
-        for c in 0..LOD_GRID_LEN as i32 {
-            let offset = IVec2::new(c - LOD_GRID_EXTENT as i32, r - LOD_GRID_EXTENT as i32);
-
-            let coord = lod.pos.round().as_ivec2() + offset;
+/// Neighbours outside of the grid are considered to have the same LOD
+fn neighbour_lods(config: &Lod2dConfig, offset: IVec2) -> [u32; 4] {
+    NEIGHBOUR_OFFSETS.map(|dir| {
+        let neighbour = offset + dir;
+        match neighbour.abs().max_element() as u32 > config.grid_extent {
+            true => offset_lod(offset),
+            false => offset_lod(neighbour),
+        }
+    })
+}
 
-            let dist = offset.abs().max_element() as usize;
-            let slot = &mut lod.map[r as usize][c as usize];
-            if slot.0[LOD_LEVELS_PER_CHUNK_EXTENT as usize].is_none() {
-                slot.0[LOD_LEVELS_PER_CHUNK_EXTENT as usize] = Some(
+pub fn spawn_chunks(mut cmds: Commands, mut lod: ResMut<Lod2dTree>) {
+    let config = lod.config;
+    let anchor = lod.pos.round().as_ivec2();
+    for r in 0..config.grid_len() {
+        for c in 0..config.grid_len() {
+            let offset = config.offset(r, c);
+            let slot = &mut lod.map[r][c];
+            if slot.current(&config).is_none() {
+                slot.0[config.levels_per_chunk_extent as usize] = Some(
                     cmds.spawn((
                         ChunkVisibility::Hidden,
                         Chunk {
-                            coord,
-                            lod: dist as u32,
+                            coord: anchor + offset,
+                            lod: offset_lod(offset),
+                            scale: 1,
+                            neighbour_lods: neighbour_lods(&config, offset),
                         },
                     ))
                     .id(),
@@ -177,19 +310,19 @@ pub fn swap_chunks(
     q_ready: Query<(), With<ChunkReady>>,
     mut invalid_slots: ResMut<InvalidSlots>,
 ) {
+    let config = tree.config;
     invalid_slots.0.retain(|offset| {
-        let [c, r] = [
-            offset.x + LOD_GRID_EXTENT as i32,
-            offset.y + LOD_GRID_EXTENT as i32,
-        ];
+        let [c, r] = (*offset + config.grid_extent as i32).to_array();
 
-        if r < 0 || r >= LOD_GRID_LEN as i32 || c < 0 || c >= LOD_GRID_LEN as i32 {
+        let len = config.grid_len() as i32;
+        if r < 0 || r >= len || c < 0 || c >= len {
             return false;
         }
 
-        let slot = tree.map[r as usize][c as usize];
+        let slot = &tree.map[r as usize][c as usize];
 
-        if slot.0[LOD_LEVELS_PER_CHUNK_EXTENT as usize]
+        if slot
+            .current(&config)
             .map(|e| q_ready.contains(e))
             .unwrap_or(false)
         {
@@ -200,7 +333,7 @@ pub fn swap_chunks(
                 .filter_map(|(i, e)| e.map(|e| (i, e)))
             {
                 let mut vis = q_chunk.get_mut(e).unwrap();
-                *vis = if i as u32 == LOD_LEVELS_PER_CHUNK_EXTENT {
+                *vis = if i as u32 == config.levels_per_chunk_extent {
                     ChunkVisibility::Visible
                 } else {
                     ChunkVisibility::Hidden
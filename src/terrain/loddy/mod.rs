@@ -1,8 +1,52 @@
 use bevy::prelude::*;
 
 pub mod d2;
+pub mod quadtree;
 mod ring;
 
+/// Where the terrain should be the most detailed, in chunks
+#[derive(Resource, Default)]
+pub struct LodCenter(pub Vec2);
+
+/// Which LOD implementation spawns the chunks
+#[derive(Resource, Default, Clone, Copy, PartialEq, Eq, Debug, Reflect)]
+#[reflect(Resource)]
+pub enum LodMode {
+    /// Fixed grid of chunks around the center, see [`d2`]
+    #[default]
+    Grid,
+    /// Nodes grow with the distance, see [`quadtree`]
+    Quadtree,
+}
+
+pub fn lod_mode_is(mode: LodMode) -> impl Fn(Res<LodMode>) -> bool {
+    move |current: Res<LodMode>| *current == mode
+}
+
+/// A terrain mesh to build. The LOD implementations spawn these with [`ChunkVisibility::Hidden`],
+/// and show them once [`ChunkReady`] has been added.
+#[derive(Component, Clone, Copy)]
+pub struct Chunk {
+    /// Position on the grid of chunks that are `scale` times the base size
+    pub coord: IVec2,
+    /// Spacing between vertices is `2^lod` times the full resolution spacing
+    pub lod: u32,
+    /// Width in base chunks, a power of two
+    pub scale: u32,
+    /// LOD of the neighbouring chunks towards -x, +x, -y and +y, so that edges can be matched
+    pub neighbour_lods: [u32; 4],
+}
+
+impl Chunk {
+    /// Center in base chunks. Base chunk `c` is centered on `c`, bigger chunks cover
+    /// `scale` base chunks starting from `coord * scale`.
+    pub fn center(&self) -> Vec2 {
+        (self.coord.as_vec2() + 0.5) * self.scale as f32 - 0.5
+    }
+}
+
+pub const NEIGHBOUR_OFFSETS: [IVec2; 4] = [IVec2::NEG_X, IVec2::X, IVec2::NEG_Y, IVec2::Y];
+
 #[derive(Component, Reflect)]
 pub enum ChunkVisibility {
     Visible,
@@ -0,0 +1,284 @@
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
+
+use super::{
+    lod_mode_is, Chunk, ChunkReady, ChunkVisibility, LodCenter, LodMode, NEIGHBOUR_OFFSETS,
+};
+
+pub struct LodQuadtreePlugin;
+
+impl Plugin for LodQuadtreePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodQuadtree>()
+            .init_resource::<LodQuadtreeConfig>()
+            .add_systems(
+                PostUpdate,
+                (
+                    clear_quadtree,
+                    (update_quadtree, swap_chunks).run_if(lod_mode_is(LodMode::Quadtree)),
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct LodQuadtreeConfig {
+    /// Level of the root nodes, they are `2^depth` base chunks wide
+    pub depth: u32,
+    /// A node is split when the center is closer to it than this many times its width
+    pub split_ratio: f32,
+}
+
+impl Default for LodQuadtreeConfig {
+    fn default() -> Self {
+        Self {
+            depth: 4,
+            split_ratio: 1.0,
+        }
+    }
+}
+
+/// A node of the quadtree, `coord` is counted in nodes of the same level
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct Node {
+    pub level: u32,
+    pub coord: IVec2,
+}
+
+impl Node {
+    /// The node of that level covering base chunk `pos`
+    fn containing(level: u32, pos: IVec2) -> Self {
+        Self {
+            level,
+            coord: pos.div_euclid(IVec2::splat(1 << level)),
+        }
+    }
+
+    fn width(&self) -> i32 {
+        1 << self.level
+    }
+
+    /// First base chunk covered by the node
+    fn min(&self) -> IVec2 {
+        self.coord * self.width()
+    }
+
+    fn children(&self) -> [Node; 4] {
+        [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE].map(|offset| Node {
+            level: self.level - 1,
+            coord: self.coord * 2 + offset,
+        })
+    }
+
+    /// Distance in base chunks from `pos` to the closest chunk of the node
+    fn distance(&self, pos: IVec2) -> i32 {
+        let min = self.min();
+        let max = min + self.width() - 1;
+        (min - pos).max(pos - max).max(IVec2::ZERO).max_element()
+    }
+}
+
+/// Leaves of the tree around the base chunk `anchor`
+fn leaves(config: &LodQuadtreeConfig, anchor: IVec2) -> HashSet<Node> {
+    let root = Node::containing(config.depth, anchor);
+    let mut stack: Vec<_> = (-1..=1)
+        .flat_map(|y| (-1..=1).map(move |x| IVec2::new(x, y)))
+        .map(|offset| Node {
+            level: config.depth,
+            coord: root.coord + offset,
+        })
+        .collect();
+
+    let mut leaves = HashSet::new();
+    while let Some(node) = stack.pop() {
+        let split_distance = config.split_ratio * node.width() as f32;
+        if node.level > 0 && (node.distance(anchor) as f32) < split_distance {
+            stack.extend(node.children());
+        } else {
+            leaves.insert(node);
+        }
+    }
+    leaves
+}
+
+/// LOD of the leaves on each side of `node`, or its own if there is none
+fn neighbour_lods(leaves: &HashSet<Node>, depth: u32, node: &Node) -> [u32; 4] {
+    let min = node.min();
+    let max = min + node.width() - 1;
+    NEIGHBOUR_OFFSETS.map(|dir| {
+        // Any base chunk along the edge works, a coarser neighbour covers all of it
+        let pos = IVec2::new(
+            if dir.x > 0 { max.x } else { min.x },
+            if dir.y > 0 { max.y } else { min.y },
+        ) + dir;
+        (0..=depth)
+            .map(|level| Node::containing(level, pos))
+            .find(|n| leaves.contains(n))
+            .map_or(node.level, |n| n.level)
+    })
+}
+
+#[derive(Resource, Default)]
+pub struct LodQuadtree {
+    /// Leaves around the current center
+    leaves: HashMap<Node, Entity>,
+    /// Chunks that stay visible until all the leaves are ready
+    retiring: Vec<Entity>,
+    /// Base chunk the leaves were computed around
+    anchor: Option<IVec2>,
+}
+
+fn clear_quadtree(mut cmds: Commands, mode: Res<LodMode>, mut tree: ResMut<LodQuadtree>) {
+    if !mode.is_changed() || *mode == LodMode::Quadtree {
+        return;
+    }
+    let tree = &mut *tree;
+    for e in tree
+        .leaves
+        .drain()
+        .map(|(_, e)| e)
+        .chain(tree.retiring.drain(..))
+    {
+        cmds.entity(e).despawn_recursive();
+    }
+    tree.anchor = None;
+}
+
+fn update_quadtree(
+    mut cmds: Commands,
+    config: Res<LodQuadtreeConfig>,
+    center: Res<LodCenter>,
+    mut tree: ResMut<LodQuadtree>,
+    mut q_chunks: Query<(&mut Chunk, &ChunkVisibility)>,
+) {
+    let anchor = center.0.round().as_ivec2();
+    if tree.anchor == Some(anchor) && !config.is_changed() {
+        return;
+    }
+    tree.anchor = Some(anchor);
+
+    let leaves = leaves(&config, anchor);
+    let mut prev_leaves = std::mem::take(&mut tree.leaves);
+    for node in &leaves {
+        let neighbour_lods = neighbour_lods(&leaves, config.depth, node);
+        let e = match prev_leaves.remove(node) {
+            Some(e) => {
+                if let Ok((mut chunk, _)) = q_chunks.get_mut(e) {
+                    if chunk.neighbour_lods != neighbour_lods {
+                        chunk.neighbour_lods = neighbour_lods;
+                    }
+                }
+                e
+            }
+            None => cmds
+                .spawn((
+                    ChunkVisibility::Hidden,
+                    Chunk {
+                        coord: node.coord,
+                        lod: node.level,
+                        scale: node.width() as u32,
+                        neighbour_lods,
+                    },
+                ))
+                .id(),
+        };
+        tree.leaves.insert(*node, e);
+    }
+
+    for e in prev_leaves.into_values() {
+        match q_chunks.get(e) {
+            Ok((_, ChunkVisibility::Visible)) => tree.retiring.push(e),
+            // Never shown, nothing to replace
+            _ => cmds.entity(e).despawn_recursive(),
+        }
+    }
+}
+
+/// Shows the new leaves all at once when they are all ready, so that there are no holes
+fn swap_chunks(
+    mut cmds: Commands,
+    mut tree: ResMut<LodQuadtree>,
+    q_ready: Query<(), With<ChunkReady>>,
+    mut q_vis: Query<&mut ChunkVisibility>,
+) {
+    if tree.retiring.is_empty() && tree.leaves.values().all(|e| {
+        q_vis
+            .get(*e)
+            .is_ok_and(|vis| matches!(vis, ChunkVisibility::Visible))
+    }) {
+        return;
+    }
+    if !tree.leaves.values().all(|e| q_ready.contains(*e)) {
+        return;
+    }
+
+    for e in tree.leaves.values() {
+        if let Ok(mut vis) = q_vis.get_mut(*e) {
+            *vis = ChunkVisibility::Visible;
+        }
+    }
+    for e in tree.retiring.drain(..) {
+        cmds.entity(e).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{leaves, neighbour_lods, LodQuadtreeConfig, Node};
+
+    #[test]
+    fn leaves_cover_the_roots_once() {
+        let config = LodQuadtreeConfig::default();
+        let anchor = IVec2::new(5, -3);
+        let leaves = leaves(&config, anchor);
+
+        let root_width = 1 << config.depth;
+        let min = Node::containing(config.depth, anchor).min() - root_width;
+        for y in 0..root_width * 3 {
+            for x in 0..root_width * 3 {
+                let pos = min + IVec2::new(x, y);
+                let covering = leaves
+                    .iter()
+                    .filter(|n| n.distance(pos) == 0)
+                    .count();
+                assert_eq!(covering, 1, "{pos}");
+            }
+        }
+    }
+
+    #[test]
+    fn detail_decreases_with_distance() {
+        let config = LodQuadtreeConfig::default();
+        let anchor = IVec2::new(-20, 7);
+        let leaves = leaves(&config, anchor);
+
+        assert!(leaves.contains(&Node::containing(0, anchor)));
+        for node in &leaves {
+            if node.level > 0 {
+                let split_distance = config.split_ratio * node.width() as f32;
+                assert!(node.distance(anchor) as f32 >= split_distance);
+            }
+        }
+        // Roots are always close enough to the anchor to be split
+        assert!(leaves.iter().any(|n| n.level == config.depth - 1));
+    }
+
+    #[test]
+    fn neighbours() {
+        let config = LodQuadtreeConfig::default();
+        let anchor = IVec2::ZERO;
+        let leaves = leaves(&config, anchor);
+        for node in &leaves {
+            // Stitching only needs to bridge one level at a time
+            let lods = neighbour_lods(&leaves, config.depth, node);
+            assert!(lods.iter().all(|lod| lod.abs_diff(node.level) <= 1), "{node:?} {lods:?}");
+        }
+        let lods = neighbour_lods(&leaves, config.depth, &Node::containing(0, anchor));
+        assert_eq!(lods, [0; 4]);
+    }
+}
@@ -0,0 +1,130 @@
+use std::ops::{Index, IndexMut};
+
+use super::{iter::Iter, iter_mut::IterMut};
+
+/// Ring buffer with a length chosen at runtime
+#[derive(Clone, Debug, Default)]
+pub struct DynRing<T> {
+    buf: Vec<T>,
+    head: usize,
+}
+
+impl<T: Default> DynRing<T> {
+    pub fn new(len: usize) -> Self {
+        Self {
+            buf: (0..len).map(|_| T::default()).collect(),
+            head: 0,
+        }
+    }
+
+    pub fn shift_right(&mut self) -> T {
+        self.head = (self.head + self.len() - 1) % self.len();
+        std::mem::take(&mut self.buf[self.head])
+    }
+
+    pub fn shift_left(&mut self) -> T {
+        let r = std::mem::take(&mut self.buf[self.head]);
+        self.head = (self.head + 1) % self.len();
+        r
+    }
+}
+
+impl<T> From<Vec<T>> for DynRing<T> {
+    fn from(buf: Vec<T>) -> Self {
+        Self { buf, head: 0 }
+    }
+}
+
+impl<T> DynRing<T> {
+    pub fn len(&self) -> usize {
+        self.buf.len()
+    }
+
+    #[allow(unused)]
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    #[allow(unused)]
+    pub fn rotate_right(&mut self) {
+        self.head = (self.head + self.len() - 1) % self.len();
+    }
+
+    #[allow(unused)]
+    pub fn rotate_left(&mut self) {
+        self.head = (self.head + 1) % self.len();
+    }
+
+    pub fn into_vec(mut self) -> Vec<T> {
+        self.buf.rotate_left(self.head);
+        self.buf
+    }
+
+    pub fn as_slices(&self) -> (&[T], &[T]) {
+        let (a, b) = self.buf.split_at(self.head);
+        (b, a)
+    }
+
+    pub fn as_mut_slices(&mut self) -> (&mut [T], &mut [T]) {
+        let (a, b) = self.buf.split_at_mut(self.head);
+        (b, a)
+    }
+
+    pub fn iter(&self) -> Iter<T> {
+        let (a, b) = self.as_slices();
+        Iter::new(a.iter(), b.iter())
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<T> {
+        let (a, b) = self.as_mut_slices();
+        IterMut::new(a.iter_mut(), b.iter_mut())
+    }
+}
+
+impl<T> IntoIterator for DynRing<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.into_vec().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DynRing<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut DynRing<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<T> Index<usize> for DynRing<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.buf[(self.head + index) % self.len()]
+    }
+}
+
+impl<T> IndexMut<usize> for DynRing<T> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        let len = self.len();
+        &mut self.buf[(self.head + index) % len]
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for DynRing<T> {
+    fn eq(&self, other: &[T]) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
@@ -1,23 +1,5 @@
 use core::{fmt, iter::FusedIterator, mem, slice};
 
-use super::Ring;
-
-impl<T, const N: usize> Ring<T, N> {
-    pub fn iter(&self) -> Iter<T> {
-        let (a, b) = self.as_slices();
-        Iter::new(a.into_iter(), b.into_iter())
-    }
-}
-
-impl<'a, T, const N: usize> IntoIterator for &'a Ring<T, N> {
-    type Item = &'a T;
-    type IntoIter = Iter<'a, T>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter()
-    }
-}
-
 pub struct Iter<'a, T: 'a> {
     i1: slice::Iter<'a, T>,
     i2: slice::Iter<'a, T>,
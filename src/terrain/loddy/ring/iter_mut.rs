@@ -1,23 +1,5 @@
 use core::{fmt, iter::FusedIterator, mem, slice};
 
-use super::Ring;
-
-impl<T, const N: usize> Ring<T, N> {
-    pub fn iter_mut(&mut self) -> IterMut<T> {
-        let (a, b) = self.as_mut_slices();
-        IterMut::new(a.into_iter(), b.into_iter())
-    }
-}
-
-impl<'a, T, const N: usize> IntoIterator for &'a mut Ring<T, N> {
-    type Item = &'a mut T;
-    type IntoIter = IterMut<'a, T>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        self.iter_mut()
-    }
-}
-
 pub struct IterMut<'a, T: 'a> {
     i1: slice::IterMut<'a, T>,
     i2: slice::IterMut<'a, T>,
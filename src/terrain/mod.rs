@@ -1,47 +1,129 @@
 use std::f32::consts::TAU;
 
-use avian3d::math::Scalar;
-use avian3d::prelude::*;
-use bevy::ecs::entity::EntityHashSet;
 use bevy::pbr::ExtendedMaterial;
 use bevy::prelude::*;
 use bevy::render::{
     mesh::Indices, render_asset::RenderAssetUsages, render_resource::PrimitiveTopology,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
 
-use noise::{NoiseFn, Perlin, Turbulence};
+use noise::{Perlin, Turbulence};
+use serde::Deserialize;
 
+mod biome;
+mod colliders;
+mod edits;
 mod loddy;
-
+mod preset;
+mod sampler;
+
+use biome::BiomeParams;
+pub use biome::Biome;
+use colliders::TerrainCollidersPlugin;
+pub use colliders::PhysicsAnchor;
+use edits::TerrainEdited;
+pub use edits::{EditLayer, TerrainEdits};
+pub use preset::{NewWorld, TerrainPreset, TerrainPresets};
+use preset::TerrainPresetLoader;
+pub use sampler::TerrainSampler;
 use loddy::{
-    d2::{Chunk, Lod2dPlugin, Lod2dTree},
-    ChunkReady, ChunkVisibility,
+    d2::{Lod2dConfig, Lod2dPlugin},
+    quadtree::LodQuadtreePlugin,
+    Chunk, ChunkReady, ChunkVisibility, LodCenter, LodMode,
 };
 
-use crate::materials::sand::{SandMaterial, SandMaterialExtension};
-
-// Makes the chunks slighly bigger so that they overlap and blend with neighboring chunks
-// This helps blend between chunks of differing LODs
-const SKIRT_RATIO: f32 = 1.2;
+use crate::{
+    materials::sand::{SandMaterial, SandMaterialExtension},
+    settings::ViewDistance,
+};
 
 pub struct TerrainPlugin;
 impl Plugin for TerrainPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(Lod2dPlugin)
+        app.add_plugins((Lod2dPlugin, LodQuadtreePlugin, TerrainCollidersPlugin))
+            .init_resource::<LodCenter>()
+            .init_resource::<LodMode>()
+            .register_type::<LodMode>()
             .init_resource::<TerrainParams>()
+            .init_asset::<TerrainPreset>()
+            .init_asset_loader::<TerrainPresetLoader>()
+            .init_resource::<TerrainPresets>()
+            .init_resource::<TerrainEdits>()
+            .init_resource::<TerrainSampler>()
+            .add_event::<TerrainEdited>()
+            .add_event::<NewWorld>()
             .register_type::<TerrainParams>()
             .register_type::<ChunkVisibility>()
             .register_type::<ChunkReady>()
             .add_systems(Startup, setup)
-            .add_systems(Update, build_terrain) // Change from Update to other
+            .configure_sets(
+                Update,
+                (
+                    PlacementSet::Clear,
+                    PlacementSet::Scenes,
+                    PlacementSet::Objects,
+                )
+                    .chain()
+                    .after(sampler::update_sampler)
+                    .run_if(on_event::<NewWorld>()),
+            )
+            .add_systems(
+                Update,
+                (
+                    preset::apply_preset,
+                    sampler::update_sampler,
+                    (mark_pending_chunks, build_terrain, finish_chunk_tasks).chain(),
+                )
+                    .chain(),
+            )
+            .add_systems(Update, keep_on_surface.after(sampler::update_sampler))
             .add_systems(Update, update_chunk_visibility)
-            .add_systems(Update, update_lod_center.before(loddy::d2::update_lod));
+            .add_systems(Update, update_lod_center)
+            .add_systems(Update, apply_view_distance);
     }
 }
 
+/// Steps of placing everything in the world on a [`NewWorld`], once the sampler has been
+/// rebuilt. The first world is placed the same way, once its preset is loaded.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum PlacementSet {
+    /// Removes what belonged to the previous world, if any, and resets the game
+    Clear,
+    /// Buildings, which level the ground under them
+    Scenes,
+    /// Placed in and around the buildings
+    Objects,
+}
+
 #[derive(Resource)]
 pub struct TerrainMaterial(Handle<SandMaterial>);
 
+/// Moves with the ground when it changes under it, like when dunes migrate
+#[derive(Component)]
+pub struct OnTerrain;
+
+/// Fraction of the sand piling up on an object that ends up burying it
+const BURIED_FRACTION: f32 = 0.3;
+
+fn keep_on_surface(
+    mut q_objects: Query<&mut Transform, With<OnTerrain>>,
+    sampler: Res<TerrainSampler>,
+    mut prev_sampler: Local<Option<TerrainSampler>>,
+) {
+    if !sampler.is_changed() {
+        return;
+    }
+    if let Some(prev_sampler) = prev_sampler.as_ref() {
+        for mut tr in &mut q_objects {
+            let pos = tr.translation.xz();
+            let delta = sampler.height(pos) - prev_sampler.height(pos);
+            tr.translation.y += delta - delta.max(0.0) * BURIED_FRACTION;
+        }
+    }
+    *prev_sampler = Some(sampler.clone());
+}
+
 fn setup(
     mut cmds: Commands,
     mut materials: ResMut<Assets<SandMaterial>>,
@@ -76,17 +158,44 @@ fn update_chunk_visibility(mut q_chunk: Query<(&ChunkVisibility, &mut Visibility
 fn update_lod_center(
     q_cam: Query<&GlobalTransform, With<Camera>>,
     tp: Res<TerrainParams>,
-    mut lod: ResMut<Lod2dTree>,
+    mut center: ResMut<LodCenter>,
 ) {
     let Ok(pos) = q_cam.get_single() else { return };
-    lod.pos = pos.translation().xz() / tp.size;
+    center.0 = pos.translation().xz() / tp.size;
+}
+
+fn apply_view_distance(
+    view_distance: Res<ViewDistance>,
+    mut mode: ResMut<LodMode>,
+    mut config: ResMut<Lod2dConfig>,
+) {
+    if !view_distance.is_changed() {
+        return;
+    }
+    let grid_extent = match *view_distance {
+        ViewDistance::Near => 1,
+        ViewDistance::Medium => 2,
+        ViewDistance::Far => 4,
+        ViewDistance::Horizon => {
+            mode.set_if_neq(LodMode::Quadtree);
+            return;
+        }
+    };
+    mode.set_if_neq(LodMode::Grid);
+    config.set_if_neq(Lod2dConfig {
+        grid_extent,
+        ..*config
+    });
 }
 
-#[derive(Resource, Reflect, Clone)]
+/// Missing fields take their default value, see [`TerrainPreset`] for the seed
+#[derive(Resource, Reflect, Clone, PartialEq, Deserialize)]
 #[reflect(Resource)]
+#[serde(default)]
 pub struct TerrainParams {
     nb_vertices: usize,
     size: f32,
+    #[serde(skip)]
     seed: u32,
     amplitude: f64,
     n_turb_frequency: f64,
@@ -95,20 +204,82 @@ pub struct TerrainParams {
     n_scale: f32,
     n_power: f32,
     n_skew: f32,
+    /// Direction dunes migrate towards
+    wind_direction: Vec2,
+    /// Distance dunes migrate every cycle, in meters
+    dune_drift: f32,
+    /// Number of storms that have moved the dunes
+    #[serde(skip)]
+    dune_cycles: u32,
+    /// Frequency of the noise selecting biomes
+    biome_scale: f32,
+    /// Biome noise values separating the bands of [`Biome::ALL`], at least `biome_blend` apart.
+    /// The noise rarely strays far from zero, bounds past 1 leave out the last biomes.
+    biome_bounds: [f32; 3],
+    /// Width of the borders between biomes, in biome noise
+    biome_blend: f32,
+    salt_pans: BiomeParams,
+    rocky_flats: BiomeParams,
+    canyons: BiomeParams,
 }
 
 impl TerrainParams {
-    pub fn get_height(&self, pos: Vec2) -> f32 {
-        let perlin = Perlin::new(self.seed);
-
-        let noise: Turbulence<Perlin, Perlin> = Turbulence::new(perlin)
+    pub fn noise(&self) -> Turbulence<Perlin, Perlin> {
+        Turbulence::new(Perlin::new(self.seed))
             .set_frequency(self.n_turb_frequency)
             .set_power(self.n_turb_power)
-            .set_roughness(self.n_turb_roughness);
+            .set_roughness(self.n_turb_roughness)
+    }
+
+    pub fn seed(&self) -> u32 {
+        self.seed
+    }
 
-        let n = noise.get((pos * self.n_scale).as_dvec2().to_array()) as f32;
+    /// Dunes move along the wind after each storm.
+    ///
+    /// This changes the params, so every terrain mesh and collider is rebuilt. That is only the
+    /// chunks in view distance and the colliders around anchors, closest to the camera first,
+    /// and it happens behind the black screen between cycles. The old meshes stay up until
+    /// their replacement is ready.
+    pub fn migrate_dunes(&mut self) {
+        self.dune_cycles += 1;
+    }
+
+    pub fn dune_cycles(&self) -> u32 {
+        self.dune_cycles
+    }
+
+    /// Puts the dunes back where they were after `cycles` storms
+    pub fn set_dune_cycles(&mut self, cycles: u32) {
+        self.dune_cycles = cycles;
+    }
+
+    pub fn dune_offset(&self) -> Vec2 {
+        self.wind_direction.normalize_or_zero() * self.dune_drift * self.dune_cycles as f32
+    }
+
+    /// The dune seas are shaped by the main noise parameters
+    pub fn biome(&self, biome: Biome) -> BiomeParams {
+        match biome {
+            Biome::SaltPan => self.salt_pans,
+            Biome::DuneSea => BiomeParams {
+                frequency: 1.0,
+                amplitude: self.amplitude as f32,
+                power: self.n_power,
+                skew: self.n_skew,
+                base: 0.0,
+                ridged: false,
+                tint: LinearRgba::NONE,
+            },
+            Biome::RockyFlats => self.rocky_flats,
+            Biome::Canyons => self.canyons,
+        }
+    }
 
-        ((n + self.n_skew).powf(self.n_power) - self.n_skew) as f32 * self.amplitude as f32
+    /// Rebuilds the noise on every call, use [`TerrainSampler`] to sample many points
+    #[allow(unused)]
+    pub fn get_height(&self, pos: Vec2) -> f32 {
+        TerrainSampler::new(self).height(pos)
     }
 }
 
@@ -125,141 +296,265 @@ impl Default for TerrainParams {
             n_scale: 0.001,
             n_power: 2.0,
             n_skew: 1.0,
+            wind_direction: Vec2::new(1.0, 0.4),
+            dune_drift: 60.0,
+            dune_cycles: 0,
+            biome_scale: 0.00025,
+            biome_bounds: [-0.4, 0.2, 0.4],
+            biome_blend: 0.1,
+            salt_pans: BiomeParams {
+                frequency: 2.0,
+                amplitude: 0.4,
+                power: 1.0,
+                skew: 0.0,
+                base: -8.0,
+                ridged: false,
+                tint: LinearRgba::new(0.9, 0.88, 0.82, 0.85),
+            },
+            rocky_flats: BiomeParams {
+                frequency: 4.0,
+                amplitude: 3.0,
+                power: 3.0,
+                skew: 0.0,
+                base: -2.0,
+                ridged: false,
+                tint: LinearRgba::new(0.35, 0.25, 0.16, 0.7),
+            },
+            canyons: BiomeParams {
+                frequency: 0.7,
+                amplitude: 40.0,
+                power: 6.0,
+                skew: 0.0,
+                base: 10.0,
+                ridged: true,
+                tint: LinearRgba::new(0.55, 0.2, 0.08, 0.6),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+impl TerrainParams {
+    /// The default params pick a random seed, tests need the same terrain every run
+    pub fn with_seed(seed: u32) -> Self {
+        Self { seed, ..default() }
+    }
+}
+
+/// Maximum number of chunk meshes being generated at the same time
+const MAX_CHUNK_TASKS: usize = 4;
+
+/// The chunk's mesh needs to be (re)generated
+#[derive(Component)]
+pub struct ChunkPending;
+
+/// Mesh generation in flight, dropping it (e.g. when the chunk is despawned) cancels it
+#[derive(Component)]
+pub struct ChunkTask(
+    #[cfg(not(target_arch = "wasm32"))] Task<Mesh>,
+    #[cfg(target_arch = "wasm32")] Option<Mesh>,
+);
+
+impl ChunkTask {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(tp: TerrainParams, sampler: TerrainSampler, chunk: Chunk) -> Self {
+        let pool = AsyncComputeTaskPool::get();
+        Self(pool.spawn(async move { create_cube_mesh(&tp, &sampler, &chunk) }))
+    }
+
+    // There are no worker threads on the web, the queue still spreads generation over frames
+    #[cfg(target_arch = "wasm32")]
+    fn spawn(tp: TerrainParams, sampler: TerrainSampler, chunk: Chunk) -> Self {
+        Self(Some(create_cube_mesh(&tp, &sampler, &chunk)))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn poll(&mut self) -> Option<Mesh> {
+        block_on(future::poll_once(&mut self.0))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn poll(&mut self) -> Option<Mesh> {
+        self.0.take()
+    }
+}
+
+fn mark_pending_chunks(
+    mut cmds: Commands,
+    q_changed_chunks: Query<Entity, Changed<Chunk>>,
+    q_chunks: Query<(Entity, &Chunk)>,
+    tp: Res<TerrainParams>,
+    mut ev_edited: EventReader<TerrainEdited>,
+) {
+    if tp.is_changed() {
+        // Tasks in flight were started with the old parameters, drop them
+        for (e, _) in &q_chunks {
+            cmds.entity(e).remove::<ChunkTask>().insert(ChunkPending);
+        }
+    } else {
+        // New chunks, or chunks whose neighbours changed LOD and need their edges rebuilt
+        for e in &q_changed_chunks {
+            cmds.entity(e).remove::<ChunkTask>().insert(ChunkPending);
+        }
+    }
+
+    for TerrainEdited(rect) in ev_edited.read() {
+        for (e, chunk) in &q_chunks {
+            let chunk_rect = Rect::from_center_size(
+                chunk.center() * tp.size,
+                Vec2::splat(chunk.scale as f32 * tp.size),
+            );
+            if !chunk_rect.intersect(*rect).is_empty() {
+                cmds.entity(e).remove::<ChunkTask>().insert(ChunkPending);
+            }
         }
     }
 }
 
 fn build_terrain(
+    mut cmds: Commands,
+    q_pending: Query<(Entity, &Chunk), (With<ChunkPending>, Without<ChunkTask>)>,
+    q_tasks: Query<(), With<ChunkTask>>,
+    tp: Res<TerrainParams>,
+    sampler: Res<TerrainSampler>,
+    center: Res<LodCenter>,
+) {
+    let free = MAX_CHUNK_TASKS.saturating_sub(q_tasks.iter().count());
+    if free == 0 {
+        return;
+    }
+
+    // Chunks closest to the LOD center first
+    let mut pending: Vec<_> = q_pending.iter().collect();
+    pending.sort_by(|(_, a), (_, b)| {
+        let dist = |c: &Chunk| c.center().distance_squared(center.0);
+        dist(a).total_cmp(&dist(b))
+    });
+
+    for (entity, chunk) in pending.into_iter().take(free) {
+        cmds.entity(entity).insert(ChunkTask::spawn(tp.clone(), sampler.clone(), *chunk));
+    }
+}
+
+fn finish_chunk_tasks(
     mut cmds: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
-    q_added_chunks: Query<Entity, Added<Chunk>>,
-    q_chunks: Query<(Entity, &Chunk)>,
+    mut q_tasks: Query<(Entity, &Chunk, &mut ChunkTask)>,
     tp: Res<TerrainParams>,
     material: Res<TerrainMaterial>,
 ) {
-    let chunks_to_build: EntityHashSet = q_added_chunks
-        .iter()
-        .chain(
-            tp.is_changed()
-                .then_some(q_chunks.iter().map(|(e, _)| e))
-                .into_iter()
-                .flatten(),
-        )
-        .collect();
-
-    for (entity, chunk) in chunks_to_build
-        .into_iter()
-        .filter_map(|e| q_chunks.get(e).ok())
-    {
-        let perlin = Perlin::new(tp.seed);
-
-        let noise: Turbulence<Perlin, Perlin> = Turbulence::new(perlin)
-            .set_frequency(tp.n_turb_frequency)
-            .set_power(tp.n_turb_power)
-            .set_roughness(tp.n_turb_roughness);
-
-        let (mesh, heights) = create_cube_mesh(&tp, &chunk, noise);
-        // Render the mesh with the custom texture using a PbrBundle, add the marker.
-        cmds.entity(entity).insert((
-            Name::new("Terrain"),
-            CollisionMargin(0.1),
-            RigidBody::Static,
-            ChunkReady,
-        ));
-
-        if chunk.lod == 0 {
-            cmds.entity(entity).insert(Collider::heightfield(
-                heights,
-                Vec3::new(tp.size * SKIRT_RATIO, 1.0, tp.size * SKIRT_RATIO),
-            ));
+    for (entity, chunk, mut task) in &mut q_tasks {
+        let Some(mesh) = task.poll() else {
+            continue;
         };
-
-        cmds.entity(entity).insert(MaterialMeshBundle {
-            mesh: meshes.add(mesh),
-            material: material.0.clone(),
-            transform: Transform::from_translation(
-                chunk.coord.as_vec2().extend(0.0).xzy() * tp.size as f32,
-            ),
-            ..default()
-        });
+        // Render the mesh with the custom texture using a PbrBundle, add the marker.
+        // Colliders are streamed separately, see `colliders`
+        cmds.entity(entity)
+            .remove::<(ChunkTask, ChunkPending)>()
+            .insert((Name::new("Terrain"), ChunkReady))
+            .insert(MaterialMeshBundle {
+                mesh: meshes.add(mesh),
+                material: material.0.clone(),
+                transform: Transform::from_translation(
+                    chunk.center().extend(0.0).xzy() * tp.size as f32,
+                ),
+                ..default()
+            });
     }
 }
 
-fn create_cube_mesh(
-    tp: &TerrainParams,
-    chunk: &Chunk,
-    noise: impl NoiseFn<f64, 2>,
-) -> (Mesh, Vec<Vec<Scalar>>) {
+fn create_cube_mesh(tp: &TerrainParams, sampler: &TerrainSampler, chunk: &Chunk) -> Mesh {
     // Keep the mesh data accessible in future frames to be able to mutate it in toggle_texture.
-    let (vertex_grid, vertex_indices, heights) = create_vertex_grid(&tp, chunk, noise);
+    let (vertex_grid, normals, colors, vertex_indices) = create_vertex_grid(&tp, sampler, chunk);
     let mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
     )
     .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertex_grid)
-    .with_inserted_indices(vertex_indices)
-    .with_computed_normals();
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    // Biome tint, see `sand.wgsl`
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(vertex_indices);
 
-    (mesh, heights)
+    mesh
 }
 
 fn create_vertex_grid(
     tp: &TerrainParams,
+    sampler: &TerrainSampler,
     chunk: &Chunk,
-    noise: impl NoiseFn<f64, 2>,
-) -> (Vec<Vec3>, Indices, Vec<Vec<Scalar>>) {
+) -> (Vec<Vec3>, Vec<Vec3>, Vec<[f32; 4]>, Indices) {
     let nb_vertices = tp.nb_vertices;
-    let size = tp.size;
-    let scale = tp.n_scale;
+    let size = tp.size * chunk.scale as f32;
+
+    let offset = chunk.center() * tp.size;
 
-    let lod = chunk.lod as usize;
-    let offset = chunk.coord.as_vec2() * size;
+    // Chunks bigger than the base size are already coarser, in proportion
+    let increment = |lod: u32| (2usize.pow(lod) / chunk.scale as usize).max(1);
 
     // let mesh_simplification_increment = match lod {
     //     0 => 1,
     //     _ => lod,
     // };
-    let mesh_simplification_increment = 2usize.pow(lod as u32);
+    let mesh_simplification_increment = increment(chunk.lod);
     // let mesh_simplification_increment = 1;
 
     let vertices_per_line =
         ((nb_vertices as f32 - 1.0) / mesh_simplification_increment as f32 + 1.0) as u32;
     // let vertices_per_line = size.x as u32;
 
+    // Position of a full resolution vertex, relative to the chunk
+    let local = |ix: usize, iy: usize| {
+        (UVec2::new(ix as u32, iy as u32).as_vec2() - nb_vertices as f32 / 2.0)
+            / nb_vertices as f32
+            * size
+    };
+
     let mut grid = vec![];
+    let mut normals = vec![];
+    let mut colors = vec![];
     let mut indices = vec![];
     let mut vidx = 0;
 
-    let mut heights = vec![];
-
     for iy in (0..=nb_vertices).step_by(mesh_simplification_increment) {
-        let mut sub_height = vec![];
         for ix in (0..=nb_vertices).step_by(mesh_simplification_increment) {
             // create vertices
-            let x = ((ix as f32) - (nb_vertices as f32 / 2.0)) / nb_vertices as f32
-                * size
-                * SKIRT_RATIO;
-            let y = ((iy as f32) - (nb_vertices as f32 / 2.0)) / nb_vertices as f32
-                * size
-                * SKIRT_RATIO;
-
-            let skirt = ((x.abs().max(y.abs()) / (size / 2.0) - 1.0).max(0.0)
-                / (SKIRT_RATIO - 1.0))
-                .powf(2.0);
-
-            let n = noise.get([
-                ((x + offset.x) * scale) as f64,
-                ((y + offset.y) * scale) as f64,
-            ]) as f32;
-
-            // blend between the noise of the terrain, and the bottom of the skirt
-            // let z = (n.abs().powf(tp.n_power as f64) * n.signum()) as f32 * amplitude as f32
-            //     - 3.0 * skirt;
-            let z = ((n + tp.n_skew).powf(tp.n_power) - tp.n_skew) as f32 * tp.amplitude as f32
-                - 3.0 * skirt;
-
-            sub_height.push(z);
-            grid.push(Vec3::new(x, z, y));
+            let xy = local(ix, iy);
+            let (mut z, mut normal) = (sampler.height(xy + offset), sampler.normal(xy + offset));
+
+            // On an edge shared with a coarser chunk, the vertices that the neighbour doesn't
+            // have are moved onto its edge, so that there are no cracks between the two
+            for (side, on_edge) in [ix == 0, ix == nb_vertices, iy == 0, iy == nb_vertices]
+                .into_iter()
+                .enumerate()
+            {
+                let neighbour_increment = increment(chunk.neighbour_lods[side]);
+                let along = if side < 2 { iy } else { ix };
+                if !on_edge || neighbour_increment <= mesh_simplification_increment {
+                    continue;
+                }
+                let rem = along % neighbour_increment;
+                if rem == 0 {
+                    continue;
+                }
+                let t = rem as f32 / neighbour_increment as f32;
+                let [a, b] = [along - rem, along - rem + neighbour_increment].map(|along| {
+                    let pos = match side < 2 {
+                        true => local(ix, along),
+                        false => local(along, iy),
+                    } + offset;
+                    (sampler.height(pos), sampler.normal(pos))
+                });
+                z = a.0.lerp(b.0, t);
+                normal = a.1.lerp(b.1, t).normalize();
+            }
+
+            grid.push(Vec3::new(xy.x, z, xy.y));
+            // Normals come from the height field rather than the triangles,
+            // so they match across chunks of different LODs
+            normals.push(normal);
+            let tint = sampler.tint(xy + offset);
+            colors.push([tint.red, tint.green, tint.blue, tint.alpha]);
 
             // create indices
             if ix < nb_vertices && iy < nb_vertices {
@@ -272,15 +567,97 @@ fn create_vertex_grid(
             }
             vidx += 1;
         }
-        heights.push(sub_height);
     }
 
-    let rows = heights.len();
-    let cols = heights[0].len();
+    (grid, normals, colors, Indices::U32(indices))
+}
 
-    let transposed: Vec<Vec<_>> = (0..rows)
-        .map(|col| (0..cols).map(|row| heights[row][col]).collect())
-        .collect();
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{create_vertex_grid, loddy::Chunk, TerrainParams, TerrainSampler};
+
+    #[test]
+    fn mesh_matches_height() {
+        let tp = TerrainParams::with_seed(1337);
+        let sampler = TerrainSampler::new(&tp);
+        for chunk in [
+            Chunk {
+                coord: IVec2::ZERO,
+                lod: 0,
+                scale: 1,
+                neighbour_lods: [0; 4],
+            },
+            Chunk {
+                coord: IVec2::new(3, -2),
+                lod: 2,
+                scale: 1,
+                neighbour_lods: [2; 4],
+            },
+            // Quadtree node covering 8x8 base chunks
+            Chunk {
+                coord: IVec2::new(-1, 2),
+                lod: 3,
+                scale: 8,
+                neighbour_lods: [3; 4],
+            },
+        ] {
+            let offset = chunk.center() * tp.size;
+            let (grid, normals, colors, _) = create_vertex_grid(&tp, &sampler, &chunk);
+            assert_eq!(grid.len(), normals.len());
+            assert_eq!(grid.len(), colors.len());
+            for v in &grid {
+                assert!(v.x.abs().max(v.z.abs()) <= tp.size * chunk.scale as f32 / 2.0);
+                let height = tp.get_height(v.xz() + offset);
+                assert!((v.y - height).abs() < 1e-4, "{} vs {height}", v.y);
+            }
+        }
+    }
 
-    (grid, Indices::U32(indices), transposed)
+    #[test]
+    fn edges_match_coarser_neighbour() {
+        let tp = TerrainParams::with_seed(1337);
+        let sampler = TerrainSampler::new(&tp);
+        let fine = Chunk {
+            coord: IVec2::ZERO,
+            lod: 0,
+            scale: 1,
+            neighbour_lods: [0, 1, 0, 0],
+        };
+        let coarse = Chunk {
+            coord: IVec2::X,
+            lod: 1,
+            scale: 1,
+            neighbour_lods: [0, 1, 1, 1],
+        };
+        let (fine_grid, _, _, _) = create_vertex_grid(&tp, &sampler, &fine);
+        let (coarse_grid, _, _, _) = create_vertex_grid(&tp, &sampler, &coarse);
+
+        // Shared edge, in world space, sorted along z
+        let edge = |grid: &[Vec3], chunk: &Chunk| {
+            let x = tp.size / 2.0 - chunk.coord.x as f32 * tp.size;
+            let mut edge: Vec<_> = grid
+                .iter()
+                .filter(|v| (v.x - x).abs() < 1e-3)
+                .map(|v| *v + chunk.coord.as_vec2().extend(0.0).xzy() * tp.size)
+                .collect();
+            edge.sort_by(|a, b| a.z.total_cmp(&b.z));
+            edge
+        };
+        let fine_edge = edge(&fine_grid, &fine);
+        let coarse_edge = edge(&coarse_grid, &coarse);
+        assert_eq!(fine_edge.len(), coarse_edge.len() * 2 - 1);
+
+        // Every fine vertex lies on the coarse edge
+        for v in &fine_edge {
+            let segment = coarse_edge
+                .windows(2)
+                .find(|w| w[0].z <= v.z && v.z <= w[1].z)
+                .unwrap();
+            let t = (v.z - segment[0].z) / (segment[1].z - segment[0].z);
+            let height = segment[0].y.lerp(segment[1].y, t);
+            assert!((v.y - height).abs() < 1e-3, "{} vs {height}", v.y);
+        }
+    }
 }
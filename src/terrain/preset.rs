@@ -0,0 +1,157 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+use serde::Deserialize;
+
+use super::{TerrainEdits, TerrainParams};
+
+/// Preset files, listed here since folders can't be loaded on the web. The first one is used
+/// until another is chosen.
+const PRESET_PATHS: [&str; 4] = [
+    "terrain/dune_sea.terrain.ron",
+    "terrain/badlands.terrain.ron",
+    "terrain/salt_flats.terrain.ron",
+    "terrain/endless_erg.terrain.ron",
+];
+
+/// Terrain parameters loaded from a `.terrain.ron` file. Edits to the file are applied to the
+/// world when the `dev` feature watches the assets.
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct TerrainPreset {
+    /// Shown in the world creation menu
+    pub name: String,
+    /// Picked at random when there is none. Editing the file keeps the current seed, so that it
+    /// only reshapes the world.
+    #[serde(default)]
+    pub seed: Option<u32>,
+    pub params: TerrainParams,
+}
+
+#[derive(Default)]
+pub struct TerrainPresetLoader;
+
+#[derive(Debug)]
+pub enum TerrainPresetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl fmt::Display for TerrainPresetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TerrainPresetError::Io(e) => write!(f, "could not read terrain preset: {e}"),
+            TerrainPresetError::Ron(e) => write!(f, "could not parse terrain preset: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TerrainPresetError {}
+
+impl From<std::io::Error> for TerrainPresetError {
+    fn from(e: std::io::Error) -> Self {
+        TerrainPresetError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for TerrainPresetError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        TerrainPresetError::Ron(e)
+    }
+}
+
+impl AssetLoader for TerrainPresetLoader {
+    type Asset = TerrainPreset;
+    type Settings = ();
+    type Error = TerrainPresetError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<TerrainPreset, TerrainPresetError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["terrain.ron"]
+    }
+}
+
+/// Sent once a preset has replaced the terrain, and the edits made on it are gone. Everything
+/// placed in the world is placed again, see [`super::PlacementSet`].
+#[derive(Event)]
+pub struct NewWorld;
+
+/// All the presets, and the one the world is generated from
+#[derive(Resource)]
+pub struct TerrainPresets {
+    pub handles: Vec<Handle<TerrainPreset>>,
+    pub selected: usize,
+}
+
+impl FromWorld for TerrainPresets {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            handles: PRESET_PATHS
+                .iter()
+                .map(|path| asset_server.load(*path))
+                .collect(),
+            selected: 0,
+        }
+    }
+}
+
+impl TerrainPresets {
+    pub fn handle(&self) -> &Handle<TerrainPreset> {
+        &self.handles[self.selected]
+    }
+}
+
+/// Regenerates the world when a preset is chosen, including the first one, or when the chosen one
+/// is reloaded with changes
+pub fn apply_preset(
+    mut ev_asset: EventReader<AssetEvent<TerrainPreset>>,
+    presets: Res<TerrainPresets>,
+    assets: Res<Assets<TerrainPreset>>,
+    mut tp: ResMut<TerrainParams>,
+    mut edits: ResMut<TerrainEdits>,
+    mut ev_new_world: EventWriter<NewWorld>,
+    // Chosen in the menu or at startup, it may still be loading
+    mut chosen: Local<bool>,
+) {
+    *chosen |= presets.is_changed();
+    let handle = presets.handle();
+    let reloaded = ev_asset
+        .read()
+        .any(|ev| ev.is_loaded_with_dependencies(handle) || ev.is_modified(handle));
+    if !reloaded && !*chosen {
+        return;
+    }
+    let Some(preset) = assets.get(handle) else {
+        return;
+    };
+    // Choosing a preset creates a new world, reloading it keeps the current one for tuning
+    let (seed, dune_cycles) = match *chosen {
+        true => (rand::random(), 0),
+        false => (tp.seed, tp.dune_cycles),
+    };
+    let params = TerrainParams {
+        seed: preset.seed.unwrap_or(seed),
+        dune_cycles,
+        ..preset.params.clone()
+    };
+    let reshaped = tp.set_if_neq(params);
+
+    // Dunes migrating keep the params otherwise, what sits on the sand follows them
+    if std::mem::take(&mut *chosen) || reshaped {
+        edits.restore(default());
+        ev_new_world.send(NewWorld);
+    }
+}
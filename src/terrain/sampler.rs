@@ -0,0 +1,260 @@
+use std::sync::Arc;
+
+use bevy::prelude::*;
+use noise::{NoiseFn, Perlin, Turbulence};
+
+use super::{
+    biome::{self, Biome, BiomeParams},
+    edits::{EditLayer, TerrainEdited, TerrainEdits},
+    TerrainParams,
+};
+
+// Step used to differentiate the noise, in noise space
+const NOISE_EPSILON: f64 = 1e-4;
+
+/// Evaluates the terrain height field. The noise is built once from the [`TerrainParams`] so that
+/// sampling many points is cheap. Includes the [`TerrainEdits`].
+///
+/// Kept up to date as a resource whenever the params or the edits change.
+#[derive(Resource, Clone)]
+pub struct TerrainSampler {
+    noise: Turbulence<Perlin, Perlin>,
+    biome_noise: Perlin,
+    scale: f32,
+    biome_scale: f32,
+    biome_bounds: [f32; 3],
+    biome_blend: f32,
+    /// In the order of [`Biome::ALL`]
+    biomes: [BiomeParams; 4],
+    dune_offset: Vec2,
+    edits: Arc<EditLayer>,
+}
+
+/// Value and derivatives of `noise` at `p`, differentiated numerically
+fn noise_gradient(noise: &impl NoiseFn<f64, 2>, p: DVec2) -> (f64, DVec2) {
+    let n = noise.get(p.to_array());
+    let dn_dx = (noise.get([p.x + NOISE_EPSILON, p.y]) - noise.get([p.x - NOISE_EPSILON, p.y]))
+        / (2.0 * NOISE_EPSILON);
+    let dn_dy = (noise.get([p.x, p.y + NOISE_EPSILON]) - noise.get([p.x, p.y - NOISE_EPSILON]))
+        / (2.0 * NOISE_EPSILON);
+    (n, DVec2::new(dn_dx, dn_dy))
+}
+
+impl TerrainSampler {
+    pub fn new(tp: &TerrainParams) -> Self {
+        Self {
+            noise: tp.noise(),
+            biome_noise: Perlin::new(tp.seed.wrapping_add(1)),
+            scale: tp.n_scale,
+            biome_scale: tp.biome_scale,
+            biome_bounds: tp.biome_bounds,
+            biome_blend: tp.biome_blend,
+            biomes: Biome::ALL.map(|biome| tp.biome(biome)),
+            dune_offset: tp.dune_offset(),
+            edits: default(),
+        }
+    }
+
+    pub fn set_edits(&mut self, edits: Arc<EditLayer>) {
+        self.edits = edits;
+    }
+
+    // Noise space position of a biome, only the dunes move by `dune_offset`
+    fn noise_pos(&self, biome: Biome, pos: Vec2) -> DVec2 {
+        let params = &self.biomes[biome as usize];
+        let pos = match biome {
+            Biome::DuneSea => pos - self.dune_offset,
+            _ => pos,
+        };
+        (pos * self.scale * params.frequency).as_dvec2()
+    }
+
+    /// Weights of the biomes at `pos` and their gradients, in the order of [`Biome::ALL`]
+    fn biome_weights(&self, pos: Vec2) -> ([f32; 4], [Vec2; 4]) {
+        let p = (pos * self.biome_scale).as_dvec2();
+        let (s, ds) = noise_gradient(&self.biome_noise, p);
+        let (w, dw_ds) = biome::weights(s as f32, self.biome_bounds, self.biome_blend);
+        (w, dw_ds.map(|dw| ds.as_vec2() * self.biome_scale * dw))
+    }
+
+    /// The biome with the most weight at `pos`
+    pub fn biome(&self, pos: Vec2) -> Biome {
+        let (w, _) = self.biome_weights(pos);
+        let i = (0..w.len()).max_by(|a, b| w[*a].total_cmp(&w[*b])).unwrap();
+        Biome::ALL[i]
+    }
+
+    /// Color of the ground at `pos`, blended between biomes. Replaces the sand by its alpha.
+    pub fn tint(&self, pos: Vec2) -> LinearRgba {
+        let (w, _) = self.biome_weights(pos);
+        let (color, alpha) =
+            self.biomes
+                .iter()
+                .zip(w)
+                .fold((Vec3::ZERO, 0.0), |(color, alpha), (params, w)| {
+                    let t = params.tint;
+                    let a = t.alpha * w;
+                    (color + Vec3::new(t.red, t.green, t.blue) * a, alpha + a)
+                });
+        let color = if alpha > 0.0 { color / alpha } else { color };
+        LinearRgba::new(color.x, color.y, color.z, alpha)
+    }
+
+    /// Procedural height, without the edits
+    pub fn base_height(&self, pos: Vec2) -> f32 {
+        let (w, _) = self.biome_weights(pos);
+        Biome::ALL
+            .into_iter()
+            .zip(w)
+            .filter(|(_, w)| *w > 0.0)
+            .map(|(biome, w)| {
+                let n = self.noise.get(self.noise_pos(biome, pos).to_array());
+                self.biomes[biome as usize].shape(n as f32).0 * w
+            })
+            .sum()
+    }
+
+    pub fn height(&self, pos: Vec2) -> f32 {
+        self.edits.height(pos, self.base_height(pos))
+    }
+
+    /// Derivative of the height along x and z.
+    ///
+    /// The height shaping and the blending of biomes are differentiated exactly, only the noise
+    /// itself (which doesn't expose its derivatives) is differentiated numerically.
+    pub fn gradient(&self, pos: Vec2) -> Vec2 {
+        let (w, dw) = self.biome_weights(pos);
+        let (height, gradient) = Biome::ALL
+            .into_iter()
+            .zip(w.into_iter().zip(dw))
+            .filter(|(_, (w, _))| *w > 0.0)
+            .fold((0.0, Vec2::ZERO), |(height, gradient), (biome, (w, dw))| {
+                let params = &self.biomes[biome as usize];
+                let (n, dn) = noise_gradient(&self.noise, self.noise_pos(biome, pos));
+                let (h, dh_dn) = params.shape(n as f32);
+                let dh = dn.as_vec2() * self.scale * params.frequency * dh_dn;
+                (height + h * w, gradient + dh * w + dw * h)
+            });
+        self.edits.gradient(pos, height, gradient)
+    }
+
+    pub fn normal(&self, pos: Vec2) -> Vec3 {
+        let g = self.gradient(pos);
+        Vec3::new(-g.x, 1.0, -g.y).normalize()
+    }
+
+    /// Angle between the ground and the horizontal, in radians
+    #[allow(unused)]
+    pub fn slope(&self, pos: Vec2) -> f32 {
+        self.gradient(pos).length().atan()
+    }
+
+    /// Heights of a `count.x` by `count.y` grid starting at `origin`, rows along x
+    pub fn sample_grid(&self, origin: Vec2, spacing: f32, count: UVec2) -> Vec<f32> {
+        (0..count.y)
+            .flat_map(|iy| (0..count.x).map(move |ix| UVec2::new(ix, iy)))
+            .map(|i| self.height(origin + i.as_vec2() * spacing))
+            .collect()
+    }
+}
+
+impl FromWorld for TerrainSampler {
+    fn from_world(world: &mut World) -> Self {
+        let mut sampler = Self::new(world.resource::<TerrainParams>());
+        sampler.set_edits(world.resource::<TerrainEdits>().layer());
+        sampler
+    }
+}
+
+/// Also announces the edited areas, once the sampler can be used to rebuild them
+pub fn update_sampler(
+    tp: Res<TerrainParams>,
+    mut edits: ResMut<TerrainEdits>,
+    mut sampler: ResMut<TerrainSampler>,
+    mut ev_edited: EventWriter<TerrainEdited>,
+) {
+    if tp.is_changed() {
+        *sampler = TerrainSampler::new(&tp);
+        sampler.set_edits(edits.layer());
+    }
+    if edits.is_changed() {
+        sampler.set_edits(edits.layer());
+        ev_edited.send_batch(edits.take_dirty().into_iter().map(TerrainEdited));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::TerrainSampler;
+    use crate::terrain::TerrainParams;
+
+    fn sampler() -> TerrainSampler {
+        TerrainSampler::new(&TerrainParams::with_seed(1337))
+    }
+
+    /// The gradient matches the slope of the height field on one side of `pos` or the other,
+    /// the ridged noise folds into a kink where it crosses zero
+    fn assert_gradient(sampler: &TerrainSampler, pos: Vec2) {
+        let step = 0.05;
+        let gradient = sampler.gradient(pos);
+        let height = sampler.height(pos);
+        for (axis, g) in [(Vec2::X, gradient.x), (Vec2::Y, gradient.y)] {
+            let forward = (sampler.height(pos + axis * step) - height) / step;
+            let backward = (height - sampler.height(pos - axis * step)) / step;
+            assert!(
+                (g - forward).abs() < 1e-2 || (g - backward).abs() < 1e-2,
+                "{pos}: {g} vs {forward} / {backward}"
+            );
+        }
+    }
+
+    #[test]
+    fn grid() {
+        let sampler = sampler();
+        let origin = Vec2::new(-40.0, 10.0);
+        let heights = sampler.sample_grid(origin, 8.0, UVec2::new(5, 3));
+        assert_eq!(heights.len(), 15);
+        assert_eq!(heights[0], sampler.height(origin));
+        assert_eq!(heights[4], sampler.height(origin + Vec2::new(32.0, 0.0)));
+        assert_eq!(heights[7], sampler.height(origin + Vec2::new(16.0, 8.0)));
+    }
+
+    #[test]
+    fn normal_follows_slope() {
+        let sampler = sampler();
+        for pos in [
+            Vec2::ZERO,
+            Vec2::new(250.0, 70.0),
+            Vec2::new(-900.0, -1200.0),
+        ] {
+            let normal = sampler.normal(pos);
+            assert!((normal.length() - 1.0).abs() < 1e-5);
+            assert!(normal.y > 0.0);
+            assert_gradient(&sampler, pos);
+        }
+    }
+
+    #[test]
+    fn blends_biomes() {
+        let sampler = sampler();
+        // Walk until crossing into another biome
+        let start = Vec2::new(70.0, -30.0);
+        let first = sampler.biome(start);
+        let border = (1..2000)
+            .map(|i| start + Vec2::new(i as f32 * 10.0, 0.0))
+            .find(|pos| sampler.biome(*pos) != first)
+            .expect("the seed has a biome border along the walk");
+        let (w, _) = sampler.biome_weights(border);
+        assert!(w.iter().filter(|w| **w > 0.0).count() >= 2, "{w:?}");
+
+        // Blending keeps the ground continuous and the gradient exact
+        for pos in [border, border - Vec2::X * 5.0, border + Vec2::new(3.0, 4.0)] {
+            assert_gradient(&sampler, pos);
+        }
+
+        let tint = sampler.tint(border);
+        assert!((0.0..=1.0).contains(&tint.alpha));
+    }
+}
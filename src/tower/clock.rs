@@ -2,16 +2,23 @@ use std::f32::consts::TAU;
 
 use bevy::prelude::*;
 
-use crate::game::{GameTime, CYCLE_LENGTH};
+use crate::{
+    game::{GameTime, CYCLE_LENGTH},
+    tween::{Ease, Timeline, Track, TweenRotation, TweenSet},
+};
 
 pub struct ClockPlugin;
 impl Plugin for ClockPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<ClockHand>().add_systems(
             Update,
-            (set_hand_initial_rotation, set_clock_time)
-                .chain()
-                .run_if(resource_exists::<GameTime>),
+            (
+                animate_hands,
+                set_clock_time
+                    .before(TweenSet)
+                    .run_if(resource_exists::<GameTime>),
+            )
+                .chain(),
         );
     }
 }
@@ -20,23 +27,27 @@ impl Plugin for ClockPlugin {
 #[reflect(Component)]
 pub struct ClockHand;
 
-#[derive(Component)]
-pub struct ClockHandInitialRotation(Quat);
-
-fn set_hand_initial_rotation(
-    mut cmds: Commands,
-    q_added_hands: Query<(Entity, &Transform), Added<ClockHand>>,
-) {
+/// The hand makes a full turn over a cycle, in quarter turns since tweens take the shortest arc
+fn animate_hands(mut cmds: Commands, q_added_hands: Query<(Entity, &Transform), Added<ClockHand>>) {
     for (e, tr) in &q_added_hands {
-        cmds.entity(e).insert(ClockHandInitialRotation(tr.rotation));
+        let track = (1..=4).fold(Track::new(tr.rotation), |track, quarter| {
+            let turns = quarter as f32 / 4.0;
+            track.to(
+                turns * CYCLE_LENGTH,
+                tr.rotation * Quat::from_rotation_z(-turns * TAU),
+                Ease::Linear,
+            )
+        });
+        cmds.entity(e).insert((
+            // Driven by the game time
+            Timeline::new(CYCLE_LENGTH).with_speed(0.0),
+            TweenRotation { timeline: e, track },
+        ));
     }
 }
 
-fn set_clock_time(
-    mut q_hand: Query<(&mut Transform, &ClockHandInitialRotation), With<ClockHand>>,
-    time: Res<GameTime>,
-) {
-    for (mut tr, initial_rotation) in &mut q_hand {
-        tr.rotation = initial_rotation.0 * Quat::from_rotation_z(-time.time / CYCLE_LENGTH * TAU);
+fn set_clock_time(mut q_hand: Query<&mut Timeline, With<ClockHand>>, time: Res<GameTime>) {
+    for mut timeline in &mut q_hand {
+        timeline.seek(time.time);
     }
 }
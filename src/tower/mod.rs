@@ -2,7 +2,11 @@ mod clock;
 
 use bevy::{audio::SpatialScale, prelude::*};
 
-use crate::terrain::TerrainParams;
+use crate::{
+    map::{LandmarkKind, MapLandmark},
+    terrain::{PlacementSet, TerrainEdits, TerrainSampler},
+    util::despawn_all,
+};
 
 pub struct TowerPlugin;
 impl Plugin for TowerPlugin {
@@ -10,7 +14,12 @@ impl Plugin for TowerPlugin {
         app.add_plugins(clock::ClockPlugin)
             .register_type::<TowerBell>()
             .add_event::<RingBell>()
-            .add_systems(Startup, setup)
+            .add_systems(Startup, load_sounds)
+            .add_systems(
+                Update,
+                despawn_all::<ClockTower>.in_set(PlacementSet::Clear),
+            )
+            .add_systems(Update, setup.in_set(PlacementSet::Scenes))
             .add_systems(Update, ring_bell);
     }
 }
@@ -22,18 +31,37 @@ pub struct BellSounds([Handle<AudioSource>; 4]);
 #[reflect(Component)]
 pub struct TowerBell;
 
-fn setup(mut cmds: Commands, asset_server: Res<AssetServer>, terrain_params: Res<TerrainParams>) {
-    let height = terrain_params.get_height(Vec2::ZERO) + 20.0;
+#[derive(Component)]
+struct ClockTower;
+
+/// Where the tower stands, close to where the game starts
+pub const TOWER_POS: Vec2 = Vec2::new(100.0, 0.0);
+// Radius of the ground levelled under the tower
+const TOWER_PAD_RADIUS: f32 = 30.0;
+
+fn setup(
+    mut cmds: Commands,
+    asset_server: Res<AssetServer>,
+    terrain: Res<TerrainSampler>,
+    mut edits: ResMut<TerrainEdits>,
+) {
+    let ground = terrain.height(TOWER_POS);
+    edits.flatten(TOWER_POS, TOWER_PAD_RADIUS, ground);
+    let height = ground + 20.0;
     cmds.spawn((
         Name::new("Clocktower"),
+        ClockTower,
+        MapLandmark(LandmarkKind::Tower),
         SceneBundle {
             scene: asset_server.load("levels/Tower.glb#Scene0"),
-            transform: Transform::from_xyz(100.0, height, 0.0)
+            transform: Transform::from_translation(TOWER_POS.extend(height).xzy())
                 .with_rotation(Quat::from_rotation_x(0.1) * Quat::from_rotation_y(0.3)),
             ..default()
         },
     ));
+}
 
+fn load_sounds(mut cmds: Commands, asset_server: Res<AssetServer>) {
     cmds.insert_resource(BellSounds([
         asset_server.load("audio/sfx/tower_bells_1.ogg"),
         asset_server.load("audio/sfx/tower_bells_2.ogg"),
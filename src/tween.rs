@@ -0,0 +1,431 @@
+use bevy::prelude::*;
+
+/// Plays [`Timeline`]s, moves what is tweened along them and fires their cues
+pub struct TweenPlugin;
+impl Plugin for TweenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<TimelineCue>().add_systems(
+            Update,
+            (
+                advance_timelines,
+                (tween_translations, tween_rotations, tween_backgrounds),
+            )
+                .chain()
+                .in_set(TweenSet),
+        );
+    }
+}
+
+/// Systems reading [`TimelineCue`] run after this set
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemSet)]
+pub struct TweenSet;
+
+/// How a tween goes from one keyframe to the next, `t` going from 0 to 1
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Ease {
+    #[default]
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    /// Starts slowly and snaps into place
+    QuartIn,
+}
+
+impl Ease {
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Ease::Linear => t,
+            Ease::QuadIn => t * t,
+            Ease::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Ease::QuadInOut => match t < 0.5 {
+                true => 2.0 * t * t,
+                false => 1.0 - 2.0 * (1.0 - t) * (1.0 - t),
+            },
+            Ease::QuartIn => t.powi(4),
+        }
+    }
+}
+
+/// Values a [`Track`] can go through
+pub trait Tweenable: Clone + Send + Sync + 'static {
+    fn tween(&self, to: &Self, t: f32) -> Self;
+}
+
+impl Tweenable for f32 {
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        self + (to - self) * t
+    }
+}
+
+impl Tweenable for Vec3 {
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        self.lerp(*to, t)
+    }
+}
+
+/// Along the shortest arc, keyframes more than half a turn apart need one in between
+impl Tweenable for Quat {
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        self.slerp(*to, t)
+    }
+}
+
+impl Tweenable for LinearRgba {
+    fn tween(&self, to: &Self, t: f32) -> Self {
+        LinearRgba::from_vec4(self.to_vec4().lerp(to.to_vec4(), t))
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Keyframe<T> {
+    time: f32,
+    value: T,
+    /// From the previous keyframe to this one
+    ease: Ease,
+}
+
+/// Keyframes of a value, in seconds along a timeline
+#[derive(Clone, Debug)]
+pub struct Track<T> {
+    start: T,
+    keys: Vec<Keyframe<T>>,
+}
+
+impl<T: Tweenable> Track<T> {
+    /// Starts with `value` at time 0
+    pub fn new(value: T) -> Self {
+        Self {
+            start: value,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Reaches `value` at `time`, keyframes are added in order
+    pub fn to(mut self, time: f32, value: T, ease: Ease) -> Self {
+        debug_assert!(time >= self.end(), "keyframes must be added in order");
+        self.keys.push(Keyframe { time, value, ease });
+        self
+    }
+
+    /// Keeps the last value until `time`
+    pub fn hold(self, time: f32) -> Self {
+        let value = self
+            .keys
+            .last()
+            .map_or(&self.start, |key| &key.value)
+            .clone();
+        self.to(time, value, Ease::Linear)
+    }
+
+    /// Time of the last keyframe
+    pub fn end(&self) -> f32 {
+        self.keys.last().map_or(0.0, |key| key.time)
+    }
+
+    pub fn sample(&self, time: f32) -> T {
+        let mut from = (0.0, &self.start);
+        for key in &self.keys {
+            if time < key.time {
+                let span = key.time - from.0;
+                let t = ((time - from.0) / span).clamp(0.0, 1.0);
+                return from.1.tween(&key.value, key.ease.apply(t));
+            }
+            from = (key.time, &key.value);
+        }
+        from.1.clone()
+    }
+}
+
+/// What happens when a timeline passes a cue
+#[derive(Clone)]
+pub enum CueAction {
+    /// Sent as a [`TimelineCue`]
+    Event(&'static str),
+    /// Played on the target entity, whichever way the timeline goes
+    Sound {
+        target: Entity,
+        source: Handle<AudioSource>,
+        settings: PlaybackSettings,
+    },
+}
+
+#[derive(Clone)]
+pub struct Cue {
+    pub time: f32,
+    pub action: CueAction,
+}
+
+/// Sent when a timeline passes an event cue
+#[derive(Event, Debug)]
+pub struct TimelineCue {
+    pub timeline: Entity,
+    pub name: &'static str,
+    /// False when the timeline plays backward
+    pub forward: bool,
+}
+
+/// Time that tweens follow, from 0 to its duration
+#[derive(Component)]
+pub struct Timeline {
+    pub elapsed: f32,
+    /// In seconds per second, negative plays it backward and 0 leaves it to be seeked
+    pub speed: f32,
+    pub duration: f32,
+    cues: Vec<Cue>,
+    // Cues between this and `elapsed` fire on the next update
+    fired_until: f32,
+}
+
+impl Timeline {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            elapsed: 0.0,
+            speed: 1.0,
+            duration,
+            cues: Vec::new(),
+            fired_until: 0.0,
+        }
+    }
+
+    pub fn with_speed(mut self, speed: f32) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn with_event(mut self, time: f32, name: &'static str) -> Self {
+        self.cues.push(Cue {
+            time,
+            action: CueAction::Event(name),
+        });
+        self
+    }
+
+    pub fn with_sound(
+        mut self,
+        time: f32,
+        target: Entity,
+        source: Handle<AudioSource>,
+        settings: PlaybackSettings,
+    ) -> Self {
+        self.cues.push(Cue {
+            time,
+            action: CueAction::Sound {
+                target,
+                source,
+                settings,
+            },
+        });
+        self
+    }
+
+    /// Jumps to `time`, cues on the way still fire
+    pub fn seek(&mut self, time: f32) {
+        self.elapsed = time.clamp(0.0, self.duration);
+    }
+
+    /// At the end it plays toward
+    pub fn finished(&self) -> bool {
+        match self.speed < 0.0 {
+            true => self.elapsed <= 0.0,
+            false => self.elapsed >= self.duration,
+        }
+    }
+
+    /// Plays for `dt` seconds, returning the cues passed since the last advance and whether
+    /// forward. A cue is passed when reached going forward, or left going backward.
+    pub fn advance(&mut self, dt: f32) -> impl Iterator<Item = (&Cue, bool)> + '_ {
+        self.seek(self.elapsed + dt * self.speed);
+        let (from, to) = (self.fired_until, self.elapsed);
+        self.fired_until = to;
+        let forward = to >= from;
+        self.cues.iter().filter_map(move |cue| {
+            let passed = match forward {
+                true => from < cue.time && cue.time <= to,
+                false => to < cue.time && cue.time <= from,
+            };
+            passed.then_some((cue, forward))
+        })
+    }
+}
+
+fn advance_timelines(
+    mut cmds: Commands,
+    time: Res<Time>,
+    mut q_timelines: Query<(Entity, &mut Timeline)>,
+    mut ev_cue: EventWriter<TimelineCue>,
+) {
+    for (e, mut timeline) in &mut q_timelines {
+        for (cue, forward) in timeline.advance(time.delta_seconds()) {
+            match &cue.action {
+                CueAction::Event(name) => {
+                    ev_cue.send(TimelineCue {
+                        timeline: e,
+                        name: *name,
+                        forward,
+                    });
+                }
+                CueAction::Sound {
+                    target,
+                    source,
+                    settings,
+                } => {
+                    if let Some(mut target) = cmds.get_entity(*target) {
+                        target.insert(AudioBundle {
+                            source: source.clone(),
+                            settings: *settings,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Moves the entity along a track of a timeline
+#[derive(Component)]
+pub struct TweenTranslation {
+    pub timeline: Entity,
+    pub track: Track<Vec3>,
+}
+
+/// Turns the entity along a track of a timeline
+#[derive(Component)]
+pub struct TweenRotation {
+    pub timeline: Entity,
+    pub track: Track<Quat>,
+}
+
+/// Fades the background of a UI node along a track of a timeline
+#[derive(Component)]
+pub struct TweenBackground {
+    pub timeline: Entity,
+    pub track: Track<LinearRgba>,
+}
+
+fn tween_translations(
+    q_timelines: Query<&Timeline>,
+    mut q_tweens: Query<(&mut Transform, &TweenTranslation)>,
+) {
+    for (mut tr, tween) in &mut q_tweens {
+        if let Ok(timeline) = q_timelines.get(tween.timeline) {
+            tr.translation = tween.track.sample(timeline.elapsed);
+        }
+    }
+}
+
+fn tween_rotations(
+    q_timelines: Query<&Timeline>,
+    mut q_tweens: Query<(&mut Transform, &TweenRotation)>,
+) {
+    for (mut tr, tween) in &mut q_tweens {
+        if let Ok(timeline) = q_timelines.get(tween.timeline) {
+            tr.rotation = tween.track.sample(timeline.elapsed);
+        }
+    }
+}
+
+fn tween_backgrounds(
+    q_timelines: Query<&Timeline>,
+    mut q_tweens: Query<(&mut BackgroundColor, &TweenBackground)>,
+) {
+    for (mut color, tween) in &mut q_tweens {
+        if let Ok(timeline) = q_timelines.get(tween.timeline) {
+            color.0 = tween.track.sample(timeline.elapsed).into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+
+    use super::{Ease, Timeline, Track};
+
+    #[test]
+    fn easing_ends() {
+        for ease in [
+            Ease::Linear,
+            Ease::QuadIn,
+            Ease::QuadOut,
+            Ease::QuadInOut,
+            Ease::QuartIn,
+        ] {
+            assert_eq!(ease.apply(0.0), 0.0);
+            assert_eq!(ease.apply(1.0), 1.0);
+            assert_eq!(ease.apply(2.0), 1.0);
+        }
+        assert_eq!(Ease::QuartIn.apply(0.5), 0.0625);
+        assert_eq!(Ease::QuadInOut.apply(0.5), 0.5);
+    }
+
+    #[test]
+    fn track_timing() {
+        let track =
+            Track::new(0.0)
+                .hold(1.0)
+                .to(2.0, 10.0, Ease::Linear)
+                .to(4.0, 0.0, Ease::QuadIn);
+        assert_eq!(track.end(), 4.0);
+        assert_eq!(track.sample(-1.0), 0.0);
+        assert_eq!(track.sample(0.5), 0.0);
+        assert_eq!(track.sample(1.5), 5.0);
+        assert_eq!(track.sample(2.0), 10.0);
+        assert_eq!(track.sample(3.0), 7.5);
+        assert_eq!(track.sample(10.0), 0.0);
+
+        let track = Track::new(Vec3::ZERO).to(2.0, Vec3::Y * 4.0, Ease::Linear);
+        assert_eq!(track.sample(1.0), Vec3::Y * 2.0);
+    }
+
+    fn fired(timeline: &mut Timeline, dt: f32) -> Vec<(f32, bool)> {
+        timeline
+            .advance(dt)
+            .map(|(cue, forward)| (cue.time, forward))
+            .collect()
+    }
+
+    #[test]
+    fn cues_fire_once() {
+        let mut timeline = Timeline::new(3.0)
+            .with_event(1.0, "a")
+            .with_event(2.0, "b")
+            .with_event(3.0, "end");
+        assert!(fired(&mut timeline, 0.5).is_empty());
+        assert_eq!(fired(&mut timeline, 0.5), [(1.0, true)]);
+        assert!(fired(&mut timeline, 0.5).is_empty());
+        // A long frame passes several cues, the end is reached exactly
+        assert_eq!(fired(&mut timeline, 5.0), [(2.0, true), (3.0, true)]);
+        assert_eq!(timeline.elapsed, 3.0);
+        assert!(timeline.finished());
+        assert!(fired(&mut timeline, 1.0).is_empty());
+    }
+
+    #[test]
+    fn cues_fire_backward() {
+        let mut timeline = Timeline::new(2.0)
+            .with_event(1.0, "a")
+            .with_event(2.0, "end");
+        fired(&mut timeline, 2.0);
+        timeline.speed = -1.0;
+        assert!(!timeline.finished());
+        // The end is left as soon as it plays back
+        assert_eq!(fired(&mut timeline, 0.5), [(2.0, false)]);
+        assert_eq!(fired(&mut timeline, 1.0), [(1.0, false)]);
+        assert!(fired(&mut timeline, 5.0).is_empty());
+        assert_eq!(timeline.elapsed, 0.0);
+        assert!(timeline.finished());
+    }
+
+    #[test]
+    fn seeking_fires_cues() {
+        let mut timeline = Timeline::new(10.0).with_speed(0.0).with_event(5.0, "a");
+        timeline.seek(4.0);
+        assert!(fired(&mut timeline, 1.0).is_empty());
+        timeline.seek(6.0);
+        assert_eq!(fired(&mut timeline, 1.0), [(5.0, true)]);
+        timeline.seek(20.0);
+        assert_eq!(timeline.elapsed, 10.0);
+    }
+}
@@ -2,6 +2,7 @@ use std::f32::consts::TAU;
 
 use bevy::{
     audio::{SpatialScale, Volume},
+    hierarchy::despawn_with_children_recursive,
     prelude::*,
     state::state::FreelyMutableState,
 };
@@ -15,7 +16,21 @@ pub fn switch_to_state<S: States + FreelyMutableState + Clone>(
     }
 }
 
-pub fn poisson_disc_sampling(
+/// Despawns every entity with `C`, along with its children. Some may already be gone with
+/// their parent.
+pub fn despawn_all<C: Component>(mut cmds: Commands, q_entities: Query<Entity, With<C>>) {
+    for e in &q_entities {
+        cmds.add(move |world: &mut World| {
+            if world.get_entity(e).is_some() {
+                despawn_with_children_recursive(world, e);
+            }
+        });
+    }
+}
+
+/// Draws from `rng`, so that the same seed gives the same points
+pub fn poisson_disc_sampling_with(
+    rng: &mut impl Rng,
     radius: f32,
     region_size: f32,
     n: usize,
@@ -39,8 +54,6 @@ pub fn poisson_disc_sampling(
         grid[idx] = Some(points.len() - 1);
     }
 
-    let mut rng = rand::thread_rng();
-
     let is_valid = |grid: &[Option<usize>], points: &[Vec2], candidate: Vec2| -> bool {
         let cell = (candidate / cell_size).as_ivec2();
         if cell != cell.clamp(IVec2::splat(0), IVec2::splat(nb_cells as i32 - 1)) {